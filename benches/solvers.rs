@@ -0,0 +1,58 @@
+//! Parse and solve timings for every day that has an input in `inputs/NN.txt`.
+//!
+//! Save a baseline before changing a solver, then compare against it:
+//!
+//! ```sh
+//! cargo bench -- --save-baseline before
+//! cargo bench -- --baseline before
+//! ```
+//!
+//! A filter such as `cargo bench -- day05/part2` runs a single benchmark.
+
+use std::fs;
+
+use aoc::{
+    solver::{self, Part},
+    Input,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+fn solvers(c: &mut Criterion) {
+    for day in solver::days() {
+        let Ok(input) = fs::read_to_string(aoc::input_path(day.number)) else {
+            continue;
+        };
+        let parsed = match day.parse(Input::from(input.as_str())) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("skipping day {}: {}", day.number, e);
+                continue;
+            }
+        };
+        let mut group = c.benchmark_group(format!("day{:02}", day.number));
+        group.bench_function("parse", |b| {
+            b.iter_batched(
+                || Input::from(input.as_str()),
+                |input| day.parse(input),
+                BatchSize::SmallInput,
+            )
+        });
+        for part in Part::ALL {
+            if let Err(e) = day.solve_parsed(part, &parsed) {
+                eprintln!("skipping day {} part {}: {}", day.number, part, e);
+                continue;
+            }
+            group.bench_function(format!("part{}", part), |b| {
+                b.iter(|| day.solve_parsed(part, &parsed))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = solvers
+}
+criterion_main!(benches);
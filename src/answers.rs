@@ -0,0 +1,106 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{solver::Part, ParseError};
+
+/// The known-good answers for one day, as recorded in `answers/NN.toml`:
+///
+/// ```toml
+/// part1 = 142
+/// part2 = "281"
+/// ```
+///
+/// Only `part1` and `part2` keys are understood, holding either an integer or
+/// a string. Either may be left out if that part has not been solved yet.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Answers {
+    pub fn get(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Answers, ParseError> {
+        let mut answers = Answers::default();
+        for (n, line) in (1..).zip(s.lines()) {
+            let content = line.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+            let (key, value) = content
+                .split_once('=')
+                .ok_or_else(|| ParseError::at(n, line, content, "expected '<key> = <value>'"))?;
+            let (key, value) = (key.trim(), value.trim());
+            let slot = match key {
+                "part1" => &mut answers.part1,
+                "part2" => &mut answers.part2,
+                _ => return Err(ParseError::at(n, line, key, "expected part1 or part2")),
+            };
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted
+                    .strip_suffix('"')
+                    .ok_or_else(|| ParseError::at(n, line, value, "unterminated string"))?,
+                None => {
+                    crate::parse::<i128>(n, line, value)?;
+                    value
+                }
+            };
+            *slot = Some(value.to_string());
+        }
+        Ok(answers)
+    }
+
+    /// Loads the answers recorded for `day`, or `None` if there is no file.
+    pub fn load(day: u8) -> io::Result<Option<Answers>> {
+        let path = answers_path(day);
+        let s = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Answers::parse(&s)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Where a day's recorded answers are kept, relative to the working directory.
+pub fn answers_path(day: u8) -> PathBuf {
+    Path::new("answers").join(format!("{:02}.toml", day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Answers;
+    use crate::{solver::Part, ParseError};
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = Answers::parse("# day 1\npart1 = 142\n\npart2 = \"281\" # checked\n");
+        let answers = answers.unwrap();
+        assert_eq!(answers.get(Part::One), Some("142"));
+        assert_eq!(answers.get(Part::Two), Some("281"));
+        assert_eq!(Answers::parse("part2 = -3").unwrap().part1, None);
+    }
+
+    #[test]
+    fn test_parse_answers_errors() {
+        assert_eq!(
+            Answers::parse("part1 = 1\npart3 = 2"),
+            Err(ParseError::new(2, 1, "part3", "expected part1 or part2"))
+        );
+        assert_eq!(
+            Answers::parse("part1 = \"12"),
+            Err(ParseError::new(1, 9, "\"12", "unterminated string"))
+        );
+        assert!(Answers::parse("part1 = twelve").is_err());
+    }
+}
@@ -1,4 +1,8 @@
-use num::integer::lcm;
+use std::collections::HashMap;
+
+use num::Integer;
+
+use crate::ParseError;
 
 #[derive(Debug)]
 struct Node {
@@ -6,70 +10,343 @@ struct Node {
     right: String,
 }
 
-type Map = std::collections::HashMap<String, Node>;
+type Map = HashMap<String, Node>;
 
-pub fn solve1(input: crate::Input) -> u64 {
-    let (directions, map) = parse_input(input);
-    search(
-        map.get_key_value("AAA").unwrap(),
-        &map,
-        directions.chars().cycle(),
-    )
+/// The left/right instructions and the map they are followed through.
+pub struct Network {
+    directions: String,
+    map: Map,
 }
 
-fn search<'a>(
-    mut cur: (&'a String, &'a Node),
-    map: &'a Map,
-    mut directions: impl Iterator<Item = char>,
-) -> u64 {
-    let mut steps = 0;
-    while !cur.0.ends_with('Z') {
-        let d = directions.next().unwrap();
-        cur = match d {
-            'L' => map.get_key_value(&cur.1.left).unwrap(),
-            'R' => map.get_key_value(&cur.1.right).unwrap(),
-            _ => unreachable!(),
-        };
-        steps += 1;
-    }
-    steps
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 8;
+    const TITLE: &'static str = "Haunted Wasteland";
+
+    type Parsed = Network;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: crate::Input) -> crate::Result<Network> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(network: &Network) -> crate::Result<u64> {
+        part1(network)
+    }
+
+    fn part2(network: &Network) -> crate::Result<u64> {
+        part2(network)
+    }
 }
 
-pub fn solve2(input: crate::Input) -> u64 {
-    let (directions, map) = parse_input(input);
-    let nodes = map
-        .iter()
-        .filter(|(k, _)| k.ends_with('A'))
+pub fn solve1(input: crate::Input) -> crate::Result<u64> {
+    part1(&parse_input(input)?)
+}
+
+/// Walks from AAA until a node ending in Z. The walk is traced as a
+/// [`Schedule`], so one that never gets there ends once it starts repeating.
+fn part1(network: &Network) -> crate::Result<u64> {
+    if !network.map.contains_key("AAA") {
+        return Err(crate::Error::Unsolvable("no node named AAA".to_string()));
+    }
+    Schedule::of("AAA", network)
+        .hits
+        .first()
+        .copied()
+        .ok_or_else(|| crate::Error::Unsolvable("no Z node can be reached from AAA".to_string()))
+}
+
+pub fn solve2(input: crate::Input) -> crate::Result<u64> {
+    part2(&parse_input(input)?)
+}
+
+fn part2(network: &Network) -> crate::Result<u64> {
+    let schedules = network
+        .map
+        .keys()
+        .filter(|k| k.ends_with('A'))
+        .map(|k| Schedule::of(k, network))
         .collect::<Vec<_>>();
-    multi_search(nodes, &map, &directions)
+    if schedules.is_empty() {
+        return Err(crate::Error::Unsolvable("no nodes ending in A".to_string()));
+    }
+    let never = || crate::Error::Unsolvable("the ghosts are never all on a Z node at once".into());
+    let steps = first_common_hit(&schedules).ok_or_else(never)?;
+    u64::try_from(steps).map_err(|_| never())
 }
 
-fn multi_search<'a>(nodes: Vec<(&'a String, &'a Node)>, map: &'a Map, directions: &str) -> u64 {
-    nodes
+/// When one ghost stands on a node ending in `Z`.
+///
+/// A ghost's state is its node together with how far through the directions
+/// it is, so after at most `nodes * directions` steps the walk repeats.
+#[derive(Debug, PartialEq, Eq)]
+struct Schedule {
+    /// The step at which the walk first enters its cycle.
+    start: u64,
+    /// How many steps the cycle takes to come round again.
+    length: u64,
+    /// Every step in `0..start + length` at which the ghost is on a Z node.
+    /// Those from `start` on come round again every `length` steps; those
+    /// before it happen once.
+    hits: Vec<u64>,
+}
+
+impl Schedule {
+    fn of(from: &str, Network { directions, map }: &Network) -> Schedule {
+        let directions = directions.as_bytes();
+        let mut seen = HashMap::new();
+        let mut hits = vec![];
+        let mut node = map.get_key_value(from).unwrap().0;
+        for step in 0.. {
+            let i = step as usize % directions.len();
+            if let Some(&start) = seen.get(&(node, i)) {
+                return Schedule {
+                    start,
+                    length: step - start,
+                    hits,
+                };
+            }
+            seen.insert((node, i), step);
+            if node.ends_with('Z') {
+                hits.push(step);
+            }
+            let next = &map[node];
+            node = match directions[i] {
+                b'L' => &next.left,
+                b'R' => &next.right,
+                _ => unreachable!(),
+            };
+        }
+        unreachable!()
+    }
+
+    fn hits_at(&self, step: u128) -> bool {
+        let (start, length) = (self.start as u128, self.length as u128);
+        self.hits.iter().any(|&hit| {
+            let hit = hit as u128;
+            if hit < start || step < start {
+                hit == step
+            } else {
+                step >= hit && (step - hit).is_multiple_of(length)
+            }
+        })
+    }
+}
+
+/// The first step at which every ghost is on a Z node.
+///
+/// A step that is one of some ghost's one-off hits can simply be checked
+/// against the others. Any other answer has every ghost inside its cycle, so
+/// it solves `step = hit (mod length)` for one cycle hit of each ghost, found
+/// with the Chinese remainder theorem for moduli that need not be coprime.
+fn first_common_hit(schedules: &[Schedule]) -> Option<u128> {
+    let one_off = schedules
+        .iter()
+        .flat_map(|s| s.hits.iter().filter(move |&&h| h < s.start))
+        .map(|&h| h as u128)
+        .filter(|&h| schedules.iter().all(|s| s.hits_at(h)))
+        .min();
+    // Each class is `step = residue (mod modulus)` with `step >= least`.
+    let mut classes = vec![(0i128, 1i128, 0i128)];
+    for s in schedules {
+        let length = s.length as i128;
+        classes = classes
+            .into_iter()
+            .flat_map(|(residue, modulus, least)| {
+                s.hits
+                    .iter()
+                    .filter(|&&h| h >= s.start)
+                    .filter_map(move |&h| {
+                        let (r, m) = crt((residue, modulus), (h as i128 % length, length))?;
+                        Some((r, m, least.max(h as i128)))
+                    })
+            })
+            .collect();
+    }
+    let cyclic = classes
         .into_iter()
-        .map(|n| search(n, map, directions.chars().cycle()))
-        .reduce(lcm)
-        .unwrap()
+        .map(|(residue, modulus, least)| {
+            let behind = (least - residue).max(0);
+            residue + Integer::div_ceil(&behind, &modulus) * modulus
+        })
+        .min()
+        .map(|step| step as u128);
+    one_off.into_iter().chain(cyclic).min()
 }
 
-fn parse_input(mut input: crate::Input) -> (String, Map) {
-    fn parse_line(l: String) -> (String, Node) {
-        let (node, lr) = l.split_once(" = ").unwrap();
-        let connections = lr
-            .trim_matches('(')
-            .trim_matches(')')
-            .split_once(", ")
-            .unwrap();
-        (
+/// Combines `x = r1 (mod m1)` and `x = r2 (mod m2)` into one congruence
+/// modulo `lcm(m1, m2)`, or `None` if no `x` satisfies both.
+fn crt((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let e = m1.extended_gcd(&m2);
+    if (r2 - r1) % e.gcd != 0 {
+        return None;
+    }
+    let lcm = (m1 / e.gcd).checked_mul(m2)?;
+    // m1 * x = gcd (mod m2), so m1 * x * (r2 - r1) / gcd = r2 - r1 (mod m2).
+    let k = ((r2 - r1) / e.gcd).checked_mul(e.x)?.rem_euclid(m2 / e.gcd);
+    Some(((r1 + m1.checked_mul(k)?).rem_euclid(lcm), lcm))
+}
+
+fn parse_input(input: crate::Input) -> Result<Network, ParseError> {
+    fn parse_line(n: usize, l: &str) -> Result<(String, Node), ParseError> {
+        let expected = || ParseError::at(n, l, l, "expected '<node> = (<left>, <right>)'");
+        let (node, lr) = l.split_once(" = ").ok_or_else(expected)?;
+        let (left, right) = lr
+            .strip_prefix('(')
+            .and_then(|lr| lr.strip_suffix(')'))
+            .and_then(|lr| lr.split_once(", "))
+            .ok_or_else(expected)?;
+        Ok((
             node.to_string(),
             Node {
-                left: connections.0.to_string(),
-                right: connections.1.to_string(),
+                left: left.to_string(),
+                right: right.to_string(),
             },
-        )
+        ))
+    }
+    let mut lines = input.numbered();
+    let (n, directions) = lines
+        .next()
+        .ok_or_else(|| ParseError::new(1, 1, "", "expected directions"))?;
+    if directions.is_empty() {
+        return Err(ParseError::new(n, 1, "", "expected directions"));
+    }
+    if let Some(i) = directions.find(|c| c != 'L' && c != 'R') {
+        let span = &directions[i..i + directions[i..].chars().next().map_or(1, char::len_utf8)];
+        return Err(ParseError::at(n, &directions, span, "expected 'L' or 'R'"));
+    }
+    let _ = lines.next();
+    let lines = lines.collect::<Vec<_>>();
+    let map = lines
+        .iter()
+        .map(|(n, l)| parse_line(*n, l))
+        .collect::<Result<Map, _>>()?;
+    for (n, l) in &lines {
+        let (_, lr) = l.split_once(" = ").expect("parsed above");
+        if let Some(missing) = lr
+            .trim_matches(|c| c == '(' || c == ')')
+            .split(", ")
+            .find(|node| !map.contains_key(*node))
+        {
+            return Err(ParseError::at(*n, l, missing, "unknown node"));
+        }
+    }
+    Ok(Network { directions, map })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crt, solve1, solve2};
+
+    #[test]
+    fn test_solve1_examples() {
+        let input = "\
+RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)";
+        assert_eq!(solve1(input.into()).unwrap(), 2);
+        let input = "\
+LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)";
+        assert_eq!(solve1(input.into()).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        let input = "\
+LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+        assert_eq!(solve2(input.into()).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_unknown_node() {
+        let e = solve1("L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 3, column 8: unknown node: \"BBB\""
+        );
+    }
+
+    #[test]
+    fn test_solve1_unreachable() {
+        let e = solve1("LR\n\nAAA = (AAA, AAA)".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "no solution: no Z node can be reached from AAA"
+        );
+    }
+
+    #[test]
+    fn test_solve2_offset_cycles() {
+        // 11 is on a Z node at every even step from 2, 22 at steps 1, 4, 7,
+        // ..., so taking the LCM of the first hits would give 2.
+        let input = "\
+L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (11Z, 11Z)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)";
+        assert_eq!(solve2(input.into()).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_solve2_hit_before_cycle() {
+        let input = "\
+L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22Z, 22Z)";
+        assert_eq!(solve2(input.into()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_solve2_never_lines_up() {
+        let input = "\
+L
+
+11A = (11Z, 11Z)
+11Z = (11A, 11A)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22B, 22B)";
+        assert_eq!(
+            solve2(input.into()).unwrap_err().to_string(),
+            "no solution: the ghosts are never all on a Z node at once"
+        );
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((2, 4), (4, 6)), Some((10, 12)));
+        assert_eq!(crt((1, 4), (2, 6)), None);
     }
-    let directions = input.next().unwrap();
-    let _ = input.next();
-    let map = Map::from_iter(input.map(parse_line));
-    (directions, map)
 }
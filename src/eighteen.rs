@@ -0,0 +1,246 @@
+use crate::{grid::Dir, Input, ParseError};
+
+/// One line of the dig plan: dig `length` metres heading `dir`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Instruction {
+    dir: Dir,
+    length: i64,
+}
+
+/// One line of the dig plan, read as written. The color is only decoded by
+/// [`PlanLine::decode`] when part 2 asks for it.
+struct PlanLine {
+    number: usize,
+    text: String,
+    written: Instruction,
+}
+
+impl PlanLine {
+    /// The instruction hidden in the color: five hex digits of length, then
+    /// one of direction.
+    fn decode(&self) -> Result<Instruction, ParseError> {
+        let (n, line) = (self.number, self.text.as_str());
+        let color = line.rsplit(' ').next().expect("split yields a field");
+        let hex = color
+            .strip_prefix("(#")
+            .and_then(|c| c.strip_suffix(')'))
+            .filter(|hex| hex.len() == 6 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| ParseError::at(n, line, color, "expected '(#' and six hex digits"))?;
+        let (length, dir) = hex.split_at(5);
+        Ok(Instruction {
+            dir: match dir {
+                "0" => Dir::East,
+                "1" => Dir::South,
+                "2" => Dir::West,
+                "3" => Dir::North,
+                _ => {
+                    return Err(ParseError::at(
+                        n,
+                        line,
+                        dir,
+                        "expected the last hex digit to be 0, 1, 2 or 3",
+                    ))
+                }
+            },
+            length: i64::from_str_radix(length, 16).expect("five hex digits fit an i64"),
+        })
+    }
+}
+
+/// The lines of the dig plan.
+pub struct DigPlan(Vec<PlanLine>);
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 18;
+    const TITLE: &'static str = "Lavaduct Lagoon";
+
+    type Parsed = DigPlan;
+    type Answer1 = i128;
+    type Answer2 = i128;
+
+    fn parse(input: Input) -> crate::Result<DigPlan> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(plan: &DigPlan) -> crate::Result<i128> {
+        part1(plan)
+    }
+
+    fn part2(plan: &DigPlan) -> crate::Result<i128> {
+        part2(plan)
+    }
+}
+
+pub fn solve1(input: Input) -> crate::Result<i128> {
+    part1(&parse_input(input)?)
+}
+
+fn part1(DigPlan(plan): &DigPlan) -> crate::Result<i128> {
+    lagoon_volume(plan.iter().map(|line| line.written))
+}
+
+pub fn solve2(input: Input) -> crate::Result<i128> {
+    part2(&parse_input(input)?)
+}
+
+fn part2(DigPlan(plan): &DigPlan) -> crate::Result<i128> {
+    let decoded = plan
+        .iter()
+        .map(PlanLine::decode)
+        .collect::<Result<Vec<_>, _>>()?;
+    lagoon_volume(decoded.into_iter())
+}
+
+/// The cubic metres the lagoon holds: the trench and everything it encloses.
+///
+/// The shoelace formula gives the area of the polygon through the middle of
+/// each trench square. Pick's theorem then counts the whole squares inside
+/// it, `area - boundary / 2 + 1`, to which the `boundary` trench squares are
+/// added. Corners are kept in `i64` and their products summed in `i128`; a
+/// plan that would overflow either has no answer here.
+fn lagoon_volume(plan: impl Iterator<Item = Instruction>) -> crate::Result<i128> {
+    let too_far = || crate::Error::Unsolvable("the trench runs too far to measure".to_string());
+    let (mut row, mut col) = (0i64, 0i64);
+    let mut twice_area = 0i128;
+    let mut boundary = 0i128;
+    for instruction in plan {
+        let length = instruction.length;
+        let (next_row, next_col) = match instruction.dir {
+            Dir::North => (row.checked_sub(length), Some(col)),
+            Dir::East => (Some(row), col.checked_add(length)),
+            Dir::South => (row.checked_add(length), Some(col)),
+            Dir::West => (Some(row), col.checked_sub(length)),
+        };
+        let (next_row, next_col) = next_row.zip(next_col).ok_or_else(too_far)?;
+        let cross = col as i128 * next_row as i128 - next_col as i128 * row as i128;
+        twice_area = twice_area.checked_add(cross).ok_or_else(too_far)?;
+        boundary += length as i128;
+        (row, col) = (next_row, next_col);
+    }
+    if (row, col) != (0, 0) {
+        return Err(crate::Error::Unsolvable(
+            "the trench does not end where it started".to_string(),
+        ));
+    }
+    Ok(twice_area.abs() / 2 + boundary / 2 + 1)
+}
+
+fn parse_input(input: Input) -> Result<DigPlan, ParseError> {
+    fn parse_line(n: usize, line: String) -> Result<PlanLine, ParseError> {
+        let mut fields = line.split(' ');
+        let (Some(dir), Some(length), Some(_color), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(ParseError::at(
+                n,
+                &line,
+                &line,
+                "expected '<direction> <length> (#<color>)'",
+            ));
+        };
+        let written = Instruction {
+            dir: match dir {
+                "U" => Dir::North,
+                "R" => Dir::East,
+                "D" => Dir::South,
+                "L" => Dir::West,
+                _ => return Err(ParseError::at(n, &line, dir, "expected U, R, D or L")),
+            },
+            length: i64::from(crate::parse::<u32>(n, &line, length)?),
+        };
+        Ok(PlanLine {
+            number: n,
+            text: line,
+            written,
+        })
+    }
+    input
+        .numbered()
+        .map(|(n, line)| parse_line(n, line))
+        .collect::<Result<_, _>>()
+        .map(DigPlan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lagoon_volume, solve1, solve2, Instruction};
+    use crate::grid::Dir;
+
+    const EXAMPLE: &str = "\
+R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 62);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 952408144115);
+    }
+
+    #[test]
+    fn test_square_counts_trench() {
+        let square = "R 2 (#000010)\nD 2 (#000011)\nL 2 (#000012)\nU 2 (#000013)";
+        assert_eq!(solve1(square.into()).unwrap(), 9);
+        assert_eq!(solve2(square.into()).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_large_lagoon() {
+        // The longest trench a color can give: 0xfffff metres a side.
+        let square = "R 1 (#fffff0)\nD 1 (#fffff1)\nL 1 (#fffff2)\nU 1 (#fffff3)";
+        let side = 0xfffff_i128 + 1;
+        assert_eq!(solve2(square.into()).unwrap(), side * side);
+    }
+
+    #[test]
+    fn test_bad_lines() {
+        let e = solve1("R 6 (#70c710)\nX 5 (#0dc571)".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 2, column 1: expected U, R, D or L: \"X\""
+        );
+        // Part 1 never reads the color.
+        let bad_color = "R 6 (#70c714)\nL 6 (#70c71x)";
+        assert_eq!(solve1(bad_color.into()).unwrap(), 7);
+        let e = solve2(bad_color.into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 1, column 12: expected the last hex digit to be 0, 1, 2 or 3: \"4\""
+        );
+        let e = solve1("R 6 (#70c710)".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the trench does not end where it started"
+        );
+    }
+
+    #[test]
+    fn test_trench_too_far() {
+        let east = Instruction {
+            dir: Dir::East,
+            length: i64::MAX,
+        };
+        let e = lagoon_volume([east, east].into_iter()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the trench runs too far to measure"
+        );
+    }
+}
@@ -1,146 +1,193 @@
-use std::collections::{HashMap, HashSet};
+use crate::{
+    grid::{Grid, Pos},
+    ParseError,
+};
 
-use petgraph::{algo::dijkstra, graphmap::UnGraphMap, visit::EdgeRef};
-
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Node {
-    row: usize,
-    col: usize,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Space {
+    Empty,
+    Galaxy,
 }
 
-impl Node {
-    fn north(&self) -> Option<Node> {
-        self.row.gt(&0).then(|| Node {
-            row: self.row - 1,
-            col: self.col,
-        })
+impl TryFrom<char> for Space {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Space::Empty),
+            '#' => Ok(Space::Galaxy),
+            c => Err(c),
+        }
     }
+}
 
-    fn south(&self) -> Option<Node> {
-        self.row.lt(&139).then(|| Node {
-            row: self.row + 1,
-            col: self.col,
-        })
+/// How many lines each empty row and each empty column of the image grows to.
+/// A weight of 1 leaves that axis as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Expansion {
+    pub rows: u64,
+    pub cols: u64,
+}
+
+impl Expansion {
+    pub const fn uniform(weight: u64) -> Expansion {
+        Expansion {
+            rows: weight,
+            cols: weight,
+        }
     }
 
-    fn east(&self) -> Option<Node> {
-        self.col.lt(&139).then(|| Node {
-            row: self.row,
-            col: self.col + 1,
-        })
+    pub const fn rows_only(weight: u64) -> Expansion {
+        Expansion {
+            rows: weight,
+            cols: 1,
+        }
     }
 
-    fn west(&self) -> Option<Node> {
-        self.col.gt(&0).then(|| Node {
-            row: self.row,
-            col: self.col - 1,
-        })
+    pub const fn cols_only(weight: u64) -> Expansion {
+        Expansion {
+            rows: 1,
+            cols: weight,
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Space {
-    Empty,
-    Galaxy,
-}
+/// Where the galaxies are in the image, before expansion.
+pub struct Image(Vec<Pos>);
 
-impl From<char> for Space {
-    fn from(c: char) -> Self {
-        match c {
-            '.' => Space::Empty,
-            '#' => Space::Galaxy,
-            _ => unreachable!(),
-        }
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 11;
+    const TITLE: &'static str = "Cosmic Expansion";
+
+    type Parsed = Image;
+    type Answer1 = u128;
+    type Answer2 = u128;
+
+    fn parse(input: crate::Input) -> crate::Result<Image> {
+        Ok(parse_input(input)?)
     }
-}
 
-type Universe = UnGraphMap<Node, u64>;
+    fn part1(image: &Image) -> crate::Result<u128> {
+        Ok(distances(image, Expansion::uniform(2)))
+    }
 
-pub fn solve1(input: crate::Input) -> u64 {
-    solve(input, 1)
+    fn part2(image: &Image) -> crate::Result<u128> {
+        Ok(distances(image, Expansion::uniform(1_000_000)))
+    }
 }
 
-pub fn solve2(input: crate::Input) -> u64 {
-    solve(input, 1_000_000)
+pub fn solve1(input: crate::Input) -> crate::Result<u128> {
+    solve(input, Expansion::uniform(2))
 }
 
-pub fn solve(input: crate::Input, empty_weight: u64) -> u64 {
-    let (universe, galaxies) = build_universe(input, empty_weight);
-    let galaxy_src_targets = galaxy_src_targets(&galaxies);
-    galaxy_src_targets
-        .into_iter()
-        .map(|(start, targets)| {
-            let paths = dijkstra(&universe, start, None, |e| *e.weight());
-            targets
-                .into_iter()
-                .map(|target| paths.get(&target).unwrap())
-                .sum::<u64>()
-        })
-        .sum()
+pub fn solve2(input: crate::Input) -> crate::Result<u128> {
+    solve(input, Expansion::uniform(1_000_000))
 }
 
-fn galaxy_src_targets(galaxies: &[Node]) -> HashMap<Node, Vec<Node>> {
-    let pairs = galaxy_pairs(galaxies);
-    let mut m = HashMap::new();
-    pairs.iter().for_each(|(n1, n2)| {
-        m.entry(*n1).or_insert_with(|| vec![]).push(*n2);
-    });
-    m
+pub fn solve(input: crate::Input, expansion: Expansion) -> crate::Result<u128> {
+    Ok(distances(&parse_input(input)?, expansion))
 }
 
-fn galaxy_pairs(galaxies: &[Node]) -> Vec<(Node, Node)> {
-    let mut pairs: Vec<_> = galaxies
-        .iter()
-        .flat_map(|n1| {
-            galaxies
-                .iter()
-                .filter_map(move |n2| (n1 != n2).then(|| (*n1.min(n2), *n1.max(n2))))
-        })
-        .collect();
-    pairs.sort();
-    pairs.dedup();
-    pairs
+/// The sum of the shortest distances between every pair of galaxies, once
+/// the rows and columns without a galaxy have grown by `expansion`.
+///
+/// Moving is free to go around galaxies, so the distance is the Manhattan
+/// distance in the expanded universe: vertical steps are charged by the rows
+/// they cross and horizontal steps by the columns.
+fn distances(Image(galaxies): &Image, expansion: Expansion) -> u128 {
+    let rows = galaxies.iter().map(|p| p.row).collect();
+    let cols = galaxies.iter().map(|p| p.col).collect();
+    axis_distances(rows, expansion.rows) + axis_distances(cols, expansion.cols)
 }
 
-fn galaxy_rows_cols(galaxies: &[Node]) -> (HashSet<usize>, HashSet<usize>) {
-    galaxies.iter().map(|node| (node.row, node.col)).unzip()
+/// Sums `|a - b|` over every pair of the galaxies' `coords` on one axis,
+/// measured after expansion.
+///
+/// With the expanded coordinates sorted as `x0 <= x1 <= ...`, the distances
+/// from `xk` to the `k` before it add up to `k * xk - (x0 + ... + x(k-1))`.
+fn axis_distances(mut coords: Vec<usize>, empty_weight: u64) -> u128 {
+    coords.sort_unstable();
+    let mut prev = coords.first().copied().unwrap_or_default();
+    let (mut expanded, mut before, mut total) = (0u128, 0u128, 0u128);
+    for (k, &c) in (0u128..).zip(&coords) {
+        // Every line strictly between two neighbouring galaxies is empty.
+        if c > prev {
+            expanded += (c - prev - 1) as u128 * empty_weight as u128 + 1;
+        }
+        prev = c;
+        total += k * expanded - before;
+        before += expanded;
+    }
+    total
 }
 
-fn build_universe(input: crate::Input, empty_weight: u64) -> (Universe, Vec<Node>) {
-    let input = parse_input(input);
-    let galaxies: Vec<_> = input
+fn parse_input(input: crate::Input) -> Result<Image, ParseError> {
+    let image = Grid::<Space>::parse(input, "expected '.' or '#'")?;
+    let galaxies = image
         .iter()
-        .filter_map(|(node, space)| Space::Galaxy.eq(space).then_some(*node))
+        .filter_map(|(pos, space)| Space::Galaxy.eq(space).then_some(pos))
         .collect();
-    let (galaxy_rows, galaxy_cols) = galaxy_rows_cols(&galaxies);
-    let edge_weight = |n: Node| -> u64 {
-        if galaxy_rows.contains(&n.row) && galaxy_cols.contains(&n.col) {
-            1
-        } else {
-            empty_weight
-        }
-    };
-    let universe = Universe::from_edges(
-        input
-            .into_iter()
-            .map(|t| t.0)
-            .map(|node| {
-                [node.north(), node.south(), node.east(), node.west()]
-                    .into_iter()
-                    .filter_map(move |o| o.map(|n| (node, n, edge_weight(n))))
-            })
-            .flatten(),
-    );
-    (universe, galaxies)
+    Ok(Image(galaxies))
 }
 
-fn parse_input(input: crate::Input) -> Vec<(Node, Space)> {
-    input
-        .enumerate()
-        .flat_map(|(row, l)| {
-            l.char_indices()
-                .map(|(col, c)| (Node { row, col }, c.into()))
-                .collect::<Vec<_>>()
-        })
-        .collect()
+#[cfg(test)]
+mod tests {
+    use super::{solve, solve1, solve2, Expansion};
+
+    const EXAMPLE: &str = "\
+...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 374);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 82000210);
+    }
+
+    #[test]
+    fn test_solve_huge_expansion() {
+        let w = u64::MAX as u128;
+        assert_eq!(
+            solve("#.\n..\n.#".into(), Expansion::uniform(u64::MAX)).unwrap(),
+            w + 2
+        );
+        assert_eq!(
+            solve("#...#".into(), Expansion::uniform(u64::MAX)).unwrap(),
+            3 * w + 1
+        );
+    }
+
+    #[test]
+    fn test_solve_larger_expansions() {
+        assert_eq!(solve(EXAMPLE.into(), Expansion::uniform(10)).unwrap(), 1030);
+        assert_eq!(
+            solve(EXAMPLE.into(), Expansion::uniform(100)).unwrap(),
+            8410
+        );
+    }
+
+    #[test]
+    fn test_solve_single_axis_expansion() {
+        let input = "#.\n..\n.#";
+        assert_eq!(solve(input.into(), Expansion::rows_only(10)).unwrap(), 12);
+        assert_eq!(solve(input.into(), Expansion::cols_only(10)).unwrap(), 3);
+        let rows = solve(EXAMPLE.into(), Expansion::rows_only(10)).unwrap();
+        let cols = solve(EXAMPLE.into(), Expansion::cols_only(10)).unwrap();
+        let none = solve(EXAMPLE.into(), Expansion::uniform(1)).unwrap();
+        assert_eq!(rows + cols - none, 1030);
+    }
 }
@@ -0,0 +1,120 @@
+use std::{
+    any::type_name,
+    fmt::{self, Display},
+    io,
+    str::FromStr,
+};
+
+/// Points at the piece of input a parser could not make sense of.
+///
+/// `line` and `column` are 1-based, as an editor would show them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(
+        line: usize,
+        column: usize,
+        text: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> ParseError {
+        ParseError {
+            line,
+            column,
+            text: text.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Reports `span`, which must be a slice of `line`, taking the column from
+    /// its position in the line.
+    pub fn at(number: usize, line: &str, span: &str, reason: impl Into<String>) -> ParseError {
+        let offset = (span.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+        let column = line
+            .get(..offset)
+            .map_or(1, |before| before.chars().count() + 1);
+        ParseError::new(number, column, span, reason)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}: {:?}",
+            self.line, self.column, self.reason, self.text
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses `span`, a slice of `line`, reporting where it was on failure.
+pub fn parse<T: FromStr>(number: usize, line: &str, span: &str) -> Result<T, ParseError> {
+    span.parse()
+        .map_err(|_| ParseError::at(number, line, span, format!("expected {}", type_name::<T>())))
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    /// The input is well formed, but does not describe a puzzle with an answer.
+    Unsolvable(String),
+    /// A parameter given for a part is unknown or has a bad value.
+    Param(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Parse(e) => write!(f, "parse error: {}", e),
+            Error::Unsolvable(reason) => write!(f, "no solution: {}", reason),
+            Error::Param(reason) => write!(f, "parameter error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Parse(e) => Some(e),
+            Error::Unsolvable(_) | Error::Param(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, ParseError};
+
+    #[test]
+    fn test_parse_error_column() {
+        let line = "Game 1: 3 blue, x red";
+        let span = &line[16..17];
+        let e = parse::<u32>(4, line, span).unwrap_err();
+        assert_eq!(e, ParseError::new(4, 17, "x", "expected u32"));
+        assert_eq!(e.to_string(), "line 4, column 17: expected u32: \"x\"");
+    }
+}
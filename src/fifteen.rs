@@ -0,0 +1,268 @@
+use crate::{Input, ParseError};
+
+const BOXES: usize = 256;
+
+/// The puzzle's HASH: each byte is added to the value, which is then
+/// multiplied by 17 and kept to one byte.
+pub fn hash(s: &str) -> u8 {
+    s.bytes()
+        .fold(0, |value, b| value.wrapping_add(b).wrapping_mul(17))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operation {
+    /// `label=focal`: put the lens in its box, replacing one with the same
+    /// label.
+    Insert(String, u8),
+    /// `label-`: take the lens with this label out of its box.
+    Remove(String),
+}
+
+/// The steps of the initialization sequence, each as written and as read.
+pub struct Steps(Vec<(String, Operation)>);
+
+/// The puzzle's HASHMAP: 256 boxes, chosen by the HASH of a label, each
+/// keeping its entries in the order they were first inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hashmap<V> {
+    boxes: Vec<Vec<(String, V)>>,
+}
+
+impl<V> Default for Hashmap<V> {
+    fn default() -> Self {
+        Hashmap {
+            boxes: (0..BOXES).map(|_| vec![]).collect(),
+        }
+    }
+}
+
+impl<V> Hashmap<V> {
+    pub fn new() -> Hashmap<V> {
+        Hashmap::default()
+    }
+
+    /// Replaces the value for `label` where it is, or adds it to the back of
+    /// its box.
+    pub fn insert(&mut self, label: &str, value: V) {
+        let entries = &mut self.boxes[hash(label) as usize];
+        match entries.iter_mut().find(|(l, _)| l == label) {
+            Some((_, v)) => *v = value,
+            None => entries.push((label.to_string(), value)),
+        }
+    }
+
+    /// Removes `label`, moving the entries behind it forward.
+    pub fn remove(&mut self, label: &str) -> Option<V> {
+        let entries = &mut self.boxes[hash(label) as usize];
+        let slot = entries.iter().position(|(l, _)| l == label)?;
+        Some(entries.remove(slot).1)
+    }
+
+    pub fn get(&self, label: &str) -> Option<&V> {
+        self.boxes[hash(label) as usize]
+            .iter()
+            .find(|(l, _)| l == label)
+            .map(|(_, v)| v)
+    }
+
+    /// Each box that holds anything, by number, with its entries in order.
+    pub fn boxes(&self) -> impl Iterator<Item = (usize, &[(String, V)])> {
+        self.boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, entries)| !entries.is_empty())
+            .map(|(n, entries)| (n, entries.as_slice()))
+    }
+
+    /// Every entry with its box and its slot in the box, box by box.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &str, &V)> {
+        self.boxes().flat_map(|(n, entries)| {
+            entries
+                .iter()
+                .enumerate()
+                .map(move |(slot, (label, v))| (n, slot, label.as_str(), v))
+        })
+    }
+}
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 15;
+    const TITLE: &'static str = "Lens Library";
+
+    type Parsed = Steps;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: Input) -> crate::Result<Steps> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(steps: &Steps) -> crate::Result<u64> {
+        Ok(part1(steps))
+    }
+
+    fn part2(steps: &Steps) -> crate::Result<u64> {
+        Ok(focusing_power(&arrange(steps)))
+    }
+}
+
+pub fn solve1(input: Input) -> crate::Result<u64> {
+    Ok(part1(&parse_input(input)?))
+}
+
+fn part1(Steps(steps): &Steps) -> u64 {
+    steps.iter().map(|(text, _)| hash(text) as u64).sum()
+}
+
+pub fn solve2(input: Input) -> crate::Result<u64> {
+    Ok(focusing_power(&arrange(&parse_input(input)?)))
+}
+
+/// The lenses in their boxes once every step has run.
+pub fn arrange(Steps(steps): &Steps) -> Hashmap<u8> {
+    let mut lenses = Hashmap::new();
+    for (_, operation) in steps {
+        match operation {
+            Operation::Insert(label, focal) => lenses.insert(label, *focal),
+            Operation::Remove(label) => {
+                lenses.remove(label);
+            }
+        }
+    }
+    lenses
+}
+
+fn focusing_power(lenses: &Hashmap<u8>) -> u64 {
+    lenses
+        .iter()
+        .map(|(n, slot, _, &focal)| (n as u64 + 1) * (slot as u64 + 1) * focal as u64)
+        .sum()
+}
+
+/// Steps are separated by commas, and newlines are ignored, so a step may
+/// run on from one line to the next. The lines are joined before splitting,
+/// and an error is reported at the line and column where its text starts.
+fn parse_input(input: Input) -> Result<Steps, ParseError> {
+    fn parse_step(line: &str, step: &str) -> Result<Operation, ParseError> {
+        if let Some(label) = step.strip_suffix('-') {
+            return Ok(Operation::Remove(label.to_string()));
+        }
+        let (label, focal) = step.split_once('=').ok_or_else(|| {
+            ParseError::at(0, line, step, "expected '<label>=<focal>' or '<label>-'")
+        })?;
+        Ok(Operation::Insert(
+            label.to_string(),
+            crate::parse(0, line, focal)?,
+        ))
+    }
+    let mut sequence = String::new();
+    // The column in `sequence` at which each line starts, with its number.
+    let mut starts = vec![];
+    for (n, line) in input.numbered() {
+        starts.push((sequence.chars().count(), n));
+        sequence.push_str(&line);
+    }
+    let mut steps = vec![];
+    if sequence.is_empty() {
+        return Ok(Steps(steps));
+    }
+    for step in sequence.split(',') {
+        let operation = parse_step(&sequence, step).map_err(|mut e| {
+            let i = starts.partition_point(|&(start, _)| start < e.column) - 1;
+            let (start, n) = starts[i];
+            (e.line, e.column) = (n, e.column - start);
+            e
+        })?;
+        steps.push((step.to_string(), operation));
+    }
+    Ok(Steps(steps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{arrange, hash, parse_input, solve1, solve2, Hashmap, Steps};
+
+    const EXAMPLE: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(hash("HASH"), 52);
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 1320);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 145);
+    }
+
+    #[test]
+    fn test_final_boxes() {
+        let lenses = arrange(&parse_input(EXAMPLE.into()).unwrap());
+        let boxes = lenses
+            .boxes()
+            .map(|(n, entries)| {
+                let entries = entries
+                    .iter()
+                    .map(|(label, focal)| format!("{} {}", label, focal))
+                    .collect::<Vec<_>>();
+                (n, entries.join(", "))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            boxes,
+            [
+                (0, "rn 1, cm 2".to_string()),
+                (3, "ot 7, ab 5, pc 6".to_string())
+            ]
+        );
+        assert_eq!(lenses.get("ot"), Some(&7));
+        assert_eq!(lenses.get("qp"), None);
+    }
+
+    #[test]
+    fn test_hashmap_keeps_order() {
+        let mut map = Hashmap::new();
+        // "rn" and "cm" share box 0.
+        map.insert("rn", 'a');
+        map.insert("cm", 'b');
+        map.insert("rn", 'c');
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [(0, 0, "rn", &'c'), (0, 1, "cm", &'b')]
+        );
+        assert_eq!(map.remove("rn"), Some('c'));
+        assert_eq!(map.remove("rn"), None);
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(0, 0, "cm", &'b')]);
+    }
+
+    #[test]
+    fn test_bad_step() {
+        let e = solve2("rn=1,cm,qp=3".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 1, column 6: expected '<label>=<focal>' or '<label>-': \"cm\""
+        );
+    }
+
+    #[test]
+    fn test_steps_run_across_lines() {
+        let Steps(steps) = parse_input("rn=1,c\nm-,qp=3\n".into()).unwrap();
+        let text = steps
+            .iter()
+            .map(|(text, _)| text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(text, ["rn=1", "cm-", "qp=3"]);
+        let e = solve1("rn=1,\ncm,qp=3".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 2, column 1: expected '<label>=<focal>' or '<label>-': \"cm\""
+        );
+        let e = solve1("rn=1,cm\n=x".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 2, column 2: expected u8: \"x\""
+        );
+    }
+}
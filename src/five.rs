@@ -1,5 +1,7 @@
 use std::ops::Range;
 
+use crate::ParseError;
+
 struct Mapping {
     dst_range: Range<u64>,
     src_range: Range<u64>,
@@ -12,10 +14,16 @@ impl Mapping {
             .then(|| self.dst_range.start + (src - self.src_range.start))
     }
 
-    fn rmap(&self, dst: u64) -> Option<u64> {
-        self.dst_range
-            .contains(&dst)
-            .then(|| self.src_range.start + (dst - self.dst_range.start))
+    /// Splits `src` into the part this mapping covers, mapped, and whatever
+    /// is left over on either side of it.
+    fn map_range(&self, src: Range<u64>) -> (Option<Range<u64>>, [Range<u64>; 2]) {
+        let start = src.start.max(self.src_range.start);
+        let end = src.end.min(self.src_range.end);
+        if start >= end {
+            return (None, [src, 0..0]);
+        }
+        let mapped = self.map(start).unwrap()..self.map(end - 1).unwrap() + 1;
+        (Some(mapped), [src.start..start, end..src.end])
     }
 }
 
@@ -26,8 +34,24 @@ impl Mappings {
         self.0.iter().find_map(|m| m.map(src)).unwrap_or(src)
     }
 
-    fn rmap(&self, dst: u64) -> u64 {
-        self.0.iter().find_map(|m| m.rmap(dst)).unwrap_or(dst)
+    /// Maps every number in `src`, giving the ranges they land in. Numbers
+    /// not covered by any mapping keep their value.
+    fn map_range(&self, src: Range<u64>) -> Vec<Range<u64>> {
+        let mut mapped = vec![];
+        let mut unmapped = vec![src];
+        for m in &self.0 {
+            unmapped = unmapped
+                .into_iter()
+                .flat_map(|r| {
+                    let (hit, rest) = m.map_range(r);
+                    mapped.extend(hit);
+                    rest
+                })
+                .filter(|r| !r.is_empty())
+                .collect();
+        }
+        mapped.extend(unmapped);
+        mapped
     }
 }
 
@@ -58,85 +82,216 @@ impl Maps {
         self.mappers().into_iter().fold(seed, |src, m| m.map(src))
     }
 
-    fn location_to_seed(&self, location: u64) -> u64 {
-        self.mappers()
-            .into_iter()
-            .rev()
-            .fold(location, |dst, m| m.rmap(dst))
+    fn seeds_to_locations(&self, seeds: Vec<Range<u64>>) -> Vec<Range<u64>> {
+        self.mappers().into_iter().fold(seeds, |src, m| {
+            src.into_iter().flat_map(|r| m.map_range(r)).collect()
+        })
+    }
+}
+
+/// The seeds to be planted and the maps that lead from them to locations.
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: Maps,
+}
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "If You Give A Seed A Fertilizer";
+
+    type Parsed = Almanac;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: crate::Input) -> crate::Result<Almanac> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(almanac: &Almanac) -> crate::Result<u64> {
+        Ok(part1(almanac))
     }
+
+    fn part2(almanac: &Almanac) -> crate::Result<u64> {
+        part2(almanac)
+    }
+}
+
+pub fn solve1(input: crate::Input) -> crate::Result<u64> {
+    Ok(part1(&parse_input(input)?))
 }
 
-pub fn solve1(input: crate::Input) -> u64 {
-    let (seeds, maps) = parse_input(input);
+fn part1(Almanac { seeds, maps, .. }: &Almanac) -> u64 {
     seeds
-        .into_iter()
-        .map(|seed| maps.seed_to_location(seed))
+        .iter()
+        .map(|seed| maps.seed_to_location(*seed))
         .min()
-        .unwrap()
+        .expect("parse_input rejects an empty seed list")
+}
+
+pub fn solve2(input: crate::Input) -> crate::Result<u64> {
+    part2(&parse_input(input)?)
 }
 
-pub fn solve2(input: crate::Input) -> u64 {
-    let (seeds, maps) = parse_input(input);
+/// Reads the seeds as `<start> <length>` pairs, which part 1 never does, so
+/// a range running past the largest `u64` is only an error here.
+fn part2(Almanac { seeds, maps }: &Almanac) -> crate::Result<u64> {
     let seeds = seeds
         .chunks_exact(2)
-        .map(|r| r[0]..r[0] + r[1])
-        .collect::<Vec<_>>();
-    let find_seed = |seed| seeds.iter().find(|r| r.contains(&seed)).is_some();
-    use rayon::prelude::*;
-    (0..u64::MAX)
-        .into_par_iter()
-        .find_first(|location| find_seed(maps.location_to_seed(*location)))
-        .unwrap()
+        .map(|pair| {
+            let end = pair[0].checked_add(pair[1]).ok_or_else(|| {
+                crate::Error::Unsolvable(format!(
+                    "the seed range '{} {}' runs past the largest number",
+                    pair[0], pair[1]
+                ))
+            })?;
+            Ok(pair[0]..end)
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+    maps.seeds_to_locations(seeds.into_iter().filter(|r| !r.is_empty()).collect())
+        .into_iter()
+        .map(|r| r.start)
+        .min()
+        .ok_or_else(|| crate::Error::Unsolvable("every seed range is empty".to_string()))
 }
 
-fn parse_input(mut input: crate::Input) -> (Vec<u64>, Maps) {
-    fn parse_mapping(line: &str) -> Mapping {
-        let mut line = line
+/// `start..start + len`, reporting `line` if the range would run past the
+/// largest `u64`.
+fn range(n: usize, line: &str, start: u64, len: u64) -> Result<Range<u64>, ParseError> {
+    let end = start
+        .checked_add(len)
+        .ok_or_else(|| ParseError::at(n, line, line, "range runs past the largest number"))?;
+    Ok(start..end)
+}
+
+fn parse_input(input: crate::Input) -> Result<Almanac, ParseError> {
+    fn parse_mapping(n: usize, line: &str) -> Result<Mapping, ParseError> {
+        let nums = line
             .split_ascii_whitespace()
-            .map(|n| n.parse::<u64>().unwrap());
-        let (dst_start, src_start, len) = (
-            line.next().unwrap(),
-            line.next().unwrap(),
-            line.next().unwrap(),
-        );
-        Mapping {
-            dst_range: dst_start..dst_start + len,
-            src_range: src_start..src_start + len,
-        }
+            .map(|s| crate::parse::<u64>(n, line, s))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [dst_start, src_start, len] = nums[..] else {
+            return Err(ParseError::at(
+                n,
+                line,
+                line,
+                "expected '<dst start> <src start> <length>'",
+            ));
+        };
+        Ok(Mapping {
+            dst_range: range(n, line, dst_start, len)?,
+            src_range: range(n, line, src_start, len)?,
+        })
     }
-    fn parse_mappings(it: &mut crate::Input) -> Mappings {
-        Mappings(
-            it.by_ref()
-                .skip(1)
-                .map_while(|line| (!line.is_empty()).then(|| parse_mapping(&line)))
-                .collect(),
-        )
+    fn parse_mappings(
+        lines: &mut impl Iterator<Item = (usize, String)>,
+    ) -> Result<Mappings, ParseError> {
+        let _header = lines.next();
+        let mappings = lines
+            .take_while(|(_, line)| !line.is_empty())
+            .map(|(n, line)| parse_mapping(n, &line))
+            .collect::<Result<_, _>>()?;
+        Ok(Mappings(mappings))
     }
-    let seeds = input
+    let mut lines = input.numbered();
+    let (n, line) = lines
         .next()
-        .map(|line| {
-            line.strip_prefix("seeds: ")
-                .unwrap()
-                .split_ascii_whitespace()
-                .map(|s| s.parse::<u64>().ok().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .unwrap();
-    let _ = input.next();
-    let it = &mut input;
+        .ok_or_else(|| ParseError::new(1, 1, "", "expected 'seeds: '"))?;
+    let seeds = line
+        .strip_prefix("seeds: ")
+        .ok_or_else(|| ParseError::at(n, &line, &line, "expected 'seeds: '"))?
+        .split_ascii_whitespace()
+        .map(|s| crate::parse::<u64>(n, &line, s))
+        .collect::<Result<Vec<_>, _>>()?;
+    if seeds.is_empty() {
+        return Err(ParseError::at(
+            n,
+            &line,
+            &line,
+            "expected at least one seed",
+        ));
+    }
+    let _ = lines.next();
+    let it = &mut lines;
     let maps = Maps {
-        seed_to_soil: parse_mappings(it),
-        soil_to_fertilizer: parse_mappings(it),
-        fertilizer_to_water: parse_mappings(it),
-        water_to_light: parse_mappings(it),
-        light_to_temperature: parse_mappings(it),
-        temperature_to_humidity: parse_mappings(it),
-        humidity_to_location: parse_mappings(it),
+        seed_to_soil: parse_mappings(it)?,
+        soil_to_fertilizer: parse_mappings(it)?,
+        fertilizer_to_water: parse_mappings(it)?,
+        water_to_light: parse_mappings(it)?,
+        light_to_temperature: parse_mappings(it)?,
+        temperature_to_humidity: parse_mappings(it)?,
+        humidity_to_location: parse_mappings(it)?,
     };
-    (seeds, maps)
+    Ok(Almanac { seeds, maps })
 }
 
+#[cfg(test)]
 mod tests {
+    use super::{solve1, solve2};
+
+    const EXAMPLE: &str = "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 35);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 46);
+    }
+
+    #[test]
+    fn test_ranges_past_u64() {
+        let seeds = "seeds: 79 14 18446744073709551615 2";
+        assert_eq!(solve1(seeds.into()).unwrap(), 2);
+        let e = solve2(seeds.into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the seed range '18446744073709551615 2' runs past the largest number"
+        );
+        let mapping = EXAMPLE.replace("50 98 2", "50 18446744073709551610 9");
+        let e = solve2(mapping.as_str().into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 4, column 1: range runs past the largest number: \"50 18446744073709551610 9\""
+        );
+    }
+
     #[test]
     fn test_mapping() {
         use super::{Mapping, Mappings};
@@ -155,4 +310,24 @@ mod tests {
         assert_eq!(ms.map(53), 55);
         assert_eq!(ms.map(10), 10);
     }
+
+    #[test]
+    fn test_map_range_splits_at_boundaries() {
+        use super::{Mapping, Mappings};
+        let ms = Mappings(vec![
+            Mapping {
+                dst_range: 50..52,
+                src_range: 98..100,
+            },
+            Mapping {
+                dst_range: 52..100,
+                src_range: 50..98,
+            },
+        ]);
+        let mut mapped = ms.map_range(40..105);
+        mapped.sort_by_key(|r| r.start);
+        assert_eq!(mapped, [40..50, 50..52, 52..100, 100..105]);
+        assert_eq!(ms.map_range(60..61), vec![62..63]);
+        assert_eq!(ms.map_range(0..10), vec![0..10]);
+    }
 }
@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 
+use crate::ParseError;
+
 #[derive(Debug)]
-struct Card {
+pub struct Card {
     winning_nums: HashSet<u64>,
     have_nums: HashSet<u64>,
 }
@@ -12,41 +14,125 @@ impl Card {
     }
 }
 
-pub fn solve1(input: crate::Input) -> u64 {
-    parse_cards(input)
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "Scratchcards";
+
+    type Parsed = Vec<Card>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: crate::Input) -> crate::Result<Vec<Card>> {
+        Ok(parse_cards(input)?)
+    }
+
+    fn part1(cards: &Vec<Card>) -> crate::Result<u64> {
+        Ok(part1(cards))
+    }
+
+    fn part2(cards: &Vec<Card>) -> crate::Result<u64> {
+        Ok(part2(cards))
+    }
+}
+
+pub fn solve1(input: crate::Input) -> crate::Result<u64> {
+    Ok(part1(&parse_cards(input)?))
+}
+
+fn part1(cards: &[Card]) -> u64 {
+    cards
+        .iter()
         .map(|card| card.matching())
-        .filter_map(|matching| matching.gt(&0).then(|| 1 << (matching - 1)))
+        .filter(|matching| matching.gt(&0))
+        .map(|matching| 1 << (matching - 1))
         .sum()
 }
 
-pub fn solve2(input: crate::Input) -> u64 {
-    let mut table = [0u64; 213];
-    parse_cards(input)
-        .map(|card| card.matching())
-        .enumerate()
-        .for_each(|(n, matching)| {
-            table[n] += 1;
-            let n_matches = table[n];
-            table[n + 1..=n + matching as usize]
-                .iter_mut()
-                .for_each(|m| *m += n_matches);
-        });
-    table.into_iter().sum()
-}
-
-fn parse_cards(input: crate::Input) -> impl Iterator<Item = Card> {
-    fn parse_line(line: &str) -> Card {
-        fn parse_set(nums: &str) -> HashSet<u64> {
+pub fn solve2(input: crate::Input) -> crate::Result<u64> {
+    Ok(part2(&parse_cards(input)?))
+}
+
+fn part2(cards: &[Card]) -> u64 {
+    count_copies(cards).into_iter().sum()
+}
+
+/// How many copies of each card end up being scratched, the original
+/// included, in card order.
+pub fn copies(input: crate::Input) -> crate::Result<Vec<u64>> {
+    Ok(count_copies(&parse_cards(input)?))
+}
+
+/// Each card wins copies of the cards after it, one per match. Wins that
+/// would reach past the last card are lost, as there is nothing to copy.
+fn count_copies(cards: &[Card]) -> Vec<u64> {
+    let mut table = vec![1u64; cards.len()];
+    for (n, card) in cards.iter().enumerate() {
+        let won = n + 1..(n + 1 + card.matching() as usize).min(cards.len());
+        let n_copies = table[n];
+        table[won].iter_mut().for_each(|m| *m += n_copies);
+    }
+    table
+}
+
+fn parse_cards(input: crate::Input) -> Result<Vec<Card>, ParseError> {
+    fn parse_line(n: usize, line: &str) -> Result<Card, ParseError> {
+        let parse_set = |nums: &str| -> Result<HashSet<u64>, ParseError> {
             nums.split_ascii_whitespace()
-                .map(|s| s.parse().unwrap())
+                .map(|s| crate::parse(n, line, s))
                 .collect()
-        }
-        let (_number, lists) = line.split_once(": ").unwrap();
-        let (winning, have) = lists.split_once(" | ").unwrap();
-        Card {
-            winning_nums: parse_set(winning),
-            have_nums: parse_set(have),
-        }
-    }
-    input.map(|line| parse_line(&line))
+        };
+        let (_number, lists) = line
+            .split_once(": ")
+            .ok_or_else(|| ParseError::at(n, line, line, "expected 'Card <n>: '"))?;
+        let (winning, have) = lists
+            .split_once(" | ")
+            .ok_or_else(|| ParseError::at(n, line, lists, "expected ' | '"))?;
+        Ok(Card {
+            winning_nums: parse_set(winning)?,
+            have_nums: parse_set(have)?,
+        })
+    }
+    input
+        .numbered()
+        .map(|(n, line)| parse_line(n, &line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copies, solve1, solve2};
+
+    const EXAMPLE: &str = "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 13);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_copies() {
+        assert_eq!(copies(EXAMPLE.into()).unwrap(), [1, 2, 4, 8, 14, 1]);
+    }
+
+    #[test]
+    fn test_wins_past_the_last_card() {
+        let input = "\
+Card 1: 1 2 3 | 1 2 3
+Card 2: 1 2 | 1 2";
+        assert_eq!(copies(input.into()).unwrap(), [1, 2]);
+        assert_eq!(solve2(input.into()).unwrap(), 3);
+    }
 }
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use crate::{
+    grid::{Dir, Grid, Pos},
+    Input, ParseError,
+};
+
+const SPIN_CYCLES: usize = 1_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Rock {
+    Empty,
+    Round,
+    Cube,
+}
+
+impl TryFrom<char> for Rock {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Rock::Empty),
+            'O' => Ok(Rock::Round),
+            '#' => Ok(Rock::Cube),
+            c => Err(c),
+        }
+    }
+}
+
+/// The platform and the rocks on it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Platform(Grid<Rock>);
+
+impl Platform {
+    /// Rolls every round rock as far as it goes towards `dir`.
+    pub fn tilt(&mut self, dir: Dir) {
+        let (width, height) = (self.0.width(), self.0.height());
+        let edge: Vec<Pos> = match dir {
+            Dir::North => (0..width).map(|col| Pos::new(0, col)).collect(),
+            Dir::South => (0..width).map(|col| Pos::new(height - 1, col)).collect(),
+            Dir::West => (0..height).map(|row| Pos::new(row, 0)).collect(),
+            Dir::East => (0..height).map(|row| Pos::new(row, width - 1)).collect(),
+        };
+        let back = dir.opposite();
+        for start in edge {
+            // The nearest place towards `dir` that the next round rock can
+            // roll to.
+            let mut free = Some(start);
+            let mut pos = Some(start);
+            while let Some(p) = pos {
+                match self.0[p] {
+                    Rock::Cube => free = self.0.step(p, back),
+                    Rock::Round => {
+                        let to = free.expect("a rock is never past the free place");
+                        self.0[p] = Rock::Empty;
+                        self.0[to] = Rock::Round;
+                        free = self.0.step(to, back);
+                    }
+                    Rock::Empty => {}
+                }
+                pos = self.0.step(p, back);
+            }
+        }
+    }
+
+    /// Tilts north, west, south and then east.
+    pub fn spin(&mut self) {
+        for dir in [Dir::North, Dir::West, Dir::South, Dir::East] {
+            self.tilt(dir);
+        }
+    }
+
+    /// The load on the north support beams: each round rock counts its
+    /// distance in rows from the south edge.
+    pub fn north_load(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|&(_, &rock)| rock == Rock::Round)
+            .map(|(pos, _)| self.0.height() - pos.row)
+            .sum()
+    }
+}
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 14;
+    const TITLE: &'static str = "Parabolic Reflector Dish";
+
+    type Parsed = Platform;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: Input) -> crate::Result<Platform> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(platform: &Platform) -> crate::Result<usize> {
+        Ok(part1(platform))
+    }
+
+    fn part2(platform: &Platform) -> crate::Result<usize> {
+        Ok(load_after(platform, SPIN_CYCLES))
+    }
+}
+
+pub fn solve1(input: Input) -> crate::Result<usize> {
+    Ok(part1(&parse_input(input)?))
+}
+
+fn part1(platform: &Platform) -> usize {
+    let mut platform = platform.clone();
+    platform.tilt(Dir::North);
+    platform.north_load()
+}
+
+pub fn solve2(input: Input) -> crate::Result<usize> {
+    Ok(load_after(&parse_input(input)?, SPIN_CYCLES))
+}
+
+/// The north load after `cycles` spin cycles. The rocks soon settle into a
+/// loop of arrangements, so the spinning stops at the first arrangement seen
+/// before and the rest is worked out from where the loop started.
+pub fn load_after(platform: &Platform, cycles: usize) -> usize {
+    let mut seen: HashMap<Platform, usize> = HashMap::new();
+    let mut loads: Vec<usize> = vec![];
+    let mut platform = platform.clone();
+    for done in 0..cycles {
+        if let Some(&start) = seen.get(&platform) {
+            return loads[start + (cycles - start) % (done - start)];
+        }
+        loads.push(platform.north_load());
+        seen.insert(platform.clone(), done);
+        platform.spin();
+    }
+    platform.north_load()
+}
+
+fn parse_input(input: Input) -> Result<Platform, ParseError> {
+    Grid::parse(input, "expected '.', 'O' or '#'").map(Platform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_after, parse_input, solve1, solve2, Platform, Rock};
+    use crate::grid::Dir;
+
+    const EXAMPLE: &str = "\
+O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+    fn rows(platform: &Platform) -> Vec<String> {
+        platform
+            .0
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|rock| match rock {
+                        Rock::Empty => '.',
+                        Rock::Round => 'O',
+                        Rock::Cube => '#',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 136);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 64);
+    }
+
+    #[test]
+    fn test_tilt() {
+        let platform = parse_input("O.#O.\n..O.O\n.O#..".into()).unwrap();
+        for (dir, expected) in [
+            (Dir::North, ["OO#OO", "..O..", "..#.."]),
+            (Dir::South, ["..#..", "..O..", "OO#OO"]),
+            (Dir::West, ["O.#O.", "OO...", "O.#.."]),
+            (Dir::East, [".O#.O", "...OO", ".O#.."]),
+        ] {
+            let mut tilted = platform.clone();
+            tilted.tilt(dir);
+            assert_eq!(rows(&tilted), expected, "{:?}", dir);
+        }
+    }
+
+    #[test]
+    fn test_load_after_matches_spinning() {
+        let platform = parse_input(EXAMPLE.into()).unwrap();
+        let mut spun = platform.clone();
+        for cycles in 0..40 {
+            assert_eq!(
+                load_after(&platform, cycles),
+                spun.north_load(),
+                "{}",
+                cycles
+            );
+            spun.spin();
+        }
+    }
+
+    #[test]
+    fn test_unknown_rock() {
+        let e = solve1("O.\n.x".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 2, column 2: expected '.', 'O' or '#': \"x\""
+        );
+    }
+}
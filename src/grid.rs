@@ -0,0 +1,331 @@
+use std::ops::{Index, IndexMut};
+
+use crate::{Input, ParseError};
+
+/// A cell of a [`Grid`], counted from the top left.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub const fn new(row: usize, col: usize) -> Pos {
+        Pos { row, col }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir {
+    /// Clockwise from north.
+    pub const ALL: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
+
+    pub fn opposite(self) -> Dir {
+        match self {
+            Dir::North => Dir::South,
+            Dir::East => Dir::West,
+            Dir::South => Dir::North,
+            Dir::West => Dir::East,
+        }
+    }
+}
+
+/// A rectangle of cells, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Parses one cell per character of each line, reporting `reason` for
+    /// any character `T` does not accept. Every row must have the same,
+    /// non-zero, width.
+    pub fn parse(input: Input, reason: &str) -> Result<Grid<T>, ParseError>
+    where
+        T: TryFrom<char>,
+    {
+        Grid::parse_lines(input.numbered(), reason)
+    }
+
+    /// As [`Grid::parse`], for lines already paired with their line numbers,
+    /// such as one block of a larger input.
+    pub fn parse_lines(
+        lines: impl IntoIterator<Item = (usize, String)>,
+        reason: &str,
+    ) -> Result<Grid<T>, ParseError>
+    where
+        T: TryFrom<char>,
+    {
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+        for (n, line) in lines {
+            if line.is_empty() {
+                return Err(ParseError::new(n, 1, line, "empty row"));
+            }
+            let before = cells.len();
+            for (col, c) in line.chars().enumerate() {
+                let cell = T::try_from(c).map_err(|_| ParseError::new(n, col + 1, c, reason))?;
+                cells.push(cell);
+            }
+            let row_width = cells.len() - before;
+            if *width.get_or_insert(row_width) != row_width {
+                return Err(ParseError::new(
+                    n,
+                    1,
+                    line,
+                    "row width differs from the first row's",
+                ));
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            cells,
+            width: width.unwrap_or_default(),
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.row * self.width + pos.col])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.row * self.width + pos.col])
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.row < self.height && pos.col < self.width
+    }
+
+    /// The cell next to `pos` in direction `dir`, if it is inside the grid.
+    pub fn step(&self, pos: Pos, dir: Dir) -> Option<Pos> {
+        let next = match dir {
+            Dir::North => Pos::new(pos.row.checked_sub(1)?, pos.col),
+            Dir::East => Pos::new(pos.row, pos.col + 1),
+            Dir::South => Pos::new(pos.row + 1, pos.col),
+            Dir::West => Pos::new(pos.row, pos.col.checked_sub(1)?),
+        };
+        self.contains(next).then_some(next)
+    }
+
+    /// The cells sharing an edge with `pos`.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Dir::ALL.into_iter().filter_map(move |d| self.step(pos, d))
+    }
+
+    /// The cells sharing an edge or a corner with `pos`.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        (-1..=1isize)
+            .flat_map(|dr| (-1..=1isize).map(move |dc| (dr, dc)))
+            .filter(|&d| d != (0, 0))
+            .filter_map(move |(dr, dc)| {
+                let row = pos.row.checked_add_signed(dr)?;
+                let col = pos.col.checked_add_signed(dc)?;
+                Some(Pos::new(row, col))
+            })
+            .filter(|&p| self.contains(p))
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| Pos::new(i / width, i % width))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn col(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "column {} out of bounds", col);
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|r| self.row(r))
+    }
+
+    pub fn cols(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|c| self.col(c))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Swaps rows for columns.
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.rebuild(self.height, self.width, |p| Pos::new(p.col, p.row))
+    }
+
+    /// Turns the grid a quarter turn clockwise, so the west edge becomes the
+    /// north edge.
+    pub fn rotate_cw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.rebuild(self.height, self.width, |p| {
+            Pos::new(self.height - 1 - p.col, p.row)
+        })
+    }
+
+    /// Turns the grid a quarter turn anticlockwise, so the east edge becomes
+    /// the north edge.
+    pub fn rotate_ccw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.rebuild(self.height, self.width, |p| {
+            Pos::new(p.col, self.width - 1 - p.row)
+        })
+    }
+
+    /// Builds a `width` by `height` grid, taking each cell from the position
+    /// in `self` that `source` gives for it.
+    fn rebuild(&self, width: usize, height: usize, source: impl Fn(Pos) -> Pos) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..width * height)
+            .map(|i| self[source(Pos::new(i / width, i % width))].clone())
+            .collect();
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dir, Grid, Pos};
+    use crate::ParseError;
+
+    fn grid(s: &str) -> Grid<char> {
+        Grid::parse(s.into(), "unused").unwrap()
+    }
+
+    fn rows(g: &Grid<char>) -> Vec<String> {
+        g.rows().map(|r| r.iter().collect()).collect()
+    }
+
+    #[test]
+    fn test_parse_sizes() {
+        let g = grid("abc\ndef");
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g[Pos::new(1, 2)], 'f');
+        assert_eq!(g.get(Pos::new(2, 0)), None);
+        assert_eq!(g.col(1).collect::<String>(), "be");
+        assert_eq!(
+            Grid::<char>::parse("abc\nde".into(), "unused"),
+            Err(ParseError::new(
+                2,
+                1,
+                "de",
+                "row width differs from the first row's"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_row() {
+        assert_eq!(
+            Grid::<char>::parse("ab\n\ncd".into(), "unused"),
+            Err(ParseError::new(2, 1, "", "empty row"))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejected_cell() {
+        #[derive(Debug)]
+        struct Digit;
+        impl TryFrom<char> for Digit {
+            type Error = ();
+            fn try_from(c: char) -> Result<Self, ()> {
+                c.is_ascii_digit().then_some(Digit).ok_or(())
+            }
+        }
+        let e = Grid::<Digit>::parse("12\n3x".into(), "expected a digit").unwrap_err();
+        assert_eq!(e, ParseError::new(2, 2, "x", "expected a digit"));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let g = grid("abc\ndef\nghi");
+        let at = |ps: Vec<Pos>| ps.into_iter().map(|p| g[p]).collect::<String>();
+        assert_eq!(at(g.neighbours4(Pos::new(0, 0)).collect()), "bd");
+        assert_eq!(at(g.neighbours4(Pos::new(1, 1)).collect()), "bfhd");
+        assert_eq!(at(g.neighbours8(Pos::new(2, 2)).collect()), "efh");
+        assert_eq!(g.neighbours8(Pos::new(1, 1)).count(), 8);
+        assert_eq!(g.step(Pos::new(0, 2), Dir::East), None);
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        let g = grid("abc\ndef");
+        assert_eq!(rows(&g.transpose()), ["ad", "be", "cf"]);
+        assert_eq!(rows(&g.rotate_cw()), ["da", "eb", "fc"]);
+        assert_eq!(rows(&g.rotate_ccw()), ["cf", "be", "ad"]);
+        assert_eq!(g.rotate_cw().rotate_ccw(), g);
+    }
+}
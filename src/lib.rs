@@ -1,29 +1,105 @@
-use std::io;
+use std::{
+    fs,
+    io::{self, BufRead},
+    iter,
+    ops::RangeFrom,
+    path::{Path, PathBuf},
+    vec,
+};
 
+pub mod answers;
 pub mod eight;
+pub mod eighteen;
 pub mod eleven;
+mod error;
+pub mod fifteen;
 pub mod five;
 pub mod four;
+pub mod fourteen;
+pub mod grid;
 pub mod nine;
 pub mod one;
 pub mod seven;
+pub mod seventeen;
 pub mod six;
+pub mod sixteen;
+pub mod solver;
 pub mod ten;
 pub mod thirteen;
 pub mod three;
 pub mod twelve;
 pub mod two;
 
-pub struct Input(io::Lines<io::StdinLock<'static>>);
+pub use error::{parse, Error, ParseError, Result};
+pub use solver::Solver;
+
+/// Puzzle input, yielded one line at a time.
+///
+/// The whole input is read up front, so any io error is reported when the
+/// `Input` is built rather than halfway through a solver.
+pub struct Input(vec::IntoIter<String>);
+
+impl Input {
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Input> {
+        reader
+            .lines()
+            .collect::<io::Result<Vec<_>>>()
+            .map(|lines| Input(lines.into_iter()))
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Input> {
+        fs::read_to_string(path).map(Input::from)
+    }
+
+    /// Pairs each line with its 1-based line number.
+    pub fn numbered(self) -> iter::Zip<RangeFrom<usize>, Input> {
+        (1..).zip(self)
+    }
+}
+
+impl From<String> for Input {
+    fn from(s: String) -> Self {
+        Input::from(s.as_str())
+    }
+}
+
+impl From<&str> for Input {
+    fn from(s: &str) -> Self {
+        Input(s.lines().map(String::from).collect::<Vec<_>>().into_iter())
+    }
+}
 
 impl Iterator for Input {
     type Item = String;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|r| r.expect("io error"))
+        self.0.next()
     }
 }
 
-pub fn read_input() -> Input {
-    Input(io::stdin().lines())
+pub fn read_input() -> io::Result<Input> {
+    Input::from_reader(io::stdin().lock())
+}
+
+/// Where a day's puzzle input is kept, relative to the working directory.
+pub fn input_path(day: u8) -> PathBuf {
+    Path::new("inputs").join(format!("{:02}.txt", day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Input;
+
+    #[test]
+    fn test_input_from_str() {
+        let lines: Vec<_> = Input::from("ab\n\ncd\n").collect();
+        assert_eq!(lines, ["ab", "", "cd"]);
+    }
+
+    #[test]
+    fn test_input_from_reader() {
+        let lines: Vec<_> = Input::from_reader(&b"1\r\n2"[..]).unwrap().collect();
+        assert_eq!(lines, ["1", "2"]);
+        assert!(Input::from_reader(&b"\xff\n"[..]).is_err());
+    }
 }
@@ -1,48 +1,223 @@
-use std::{env::args, fmt::Display, process, time::Instant};
-
-macro_rules! generate_runner {
-    ($(($n:literal, $m:ident)),+) => {
-        fn run(problem: &str, input: aoc::Input) -> Result<Box<dyn Display>, String> {
-            match problem {
-                $(
-                    concat!($n,1) => Ok(Box::new(aoc::$m::solve1(input))),
-                    concat!($n,2) => Ok(Box::new(aoc::$m::solve2(input))),
-                )+
-                _ => Err(format!("invalid problem number: {}", problem)),
+use std::{
+    env::args,
+    fs,
+    ops::RangeInclusive,
+    process,
+    time::{Duration, Instant},
+};
+
+use aoc::{
+    answers::{self, Answers},
+    solver::{self, Day, Params, Part},
+};
+
+const USAGE: &str = "usage:
+    aoc <day><part> [--<param> <value>]... [input]
+                               solve one part, reading stdin if no input is given
+    aoc all                    solve every day from inputs/NN.txt
+    aoc <first>..=<last>       solve a range of days from inputs/NN.txt
+    aoc verify [day]           check answers against those recorded in answers/NN.toml";
+
+fn parse_problem(problem: &str) -> Result<(&'static Day, Part), String> {
+    let invalid = || format!("invalid problem number: {}", problem);
+    if problem.len() < 2 || !problem.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let (day, part) = problem.split_at(problem.len() - 1);
+    let day = day.parse().ok().and_then(solver::day).ok_or_else(invalid)?;
+    let part = part
+        .parse()
+        .ok()
+        .and_then(Part::from_number)
+        .ok_or_else(invalid)?;
+    Ok((day, part))
+}
+
+/// Parses `a..=b` or `a..b` into an inclusive range of day numbers.
+fn parse_days(days: &str) -> Result<RangeInclusive<u8>, String> {
+    let invalid = || format!("invalid day range: {}", days);
+    let (first, last) = days.split_once("..").ok_or_else(invalid)?;
+    let first = first.parse::<u8>().map_err(|_| invalid())?;
+    let last = match last.strip_prefix('=') {
+        Some(last) => last.parse::<u8>().map_err(|_| invalid())?,
+        None => last
+            .parse::<u8>()
+            .ok()
+            .and_then(|last| last.checked_sub(1))
+            .ok_or_else(invalid)?,
+    };
+    Ok(first..=last)
+}
+
+/// Splits the arguments after the problem into `--<name> <value>` parameters
+/// and at most one input path.
+fn parse_params(args: &[String]) -> Result<(Params, Option<&String>), String> {
+    let mut params = vec![];
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--") {
+            Some(name) => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("expected a value for --{}", name))?;
+                params.push((name.to_string(), value.clone()));
             }
+            None if path.is_none() => path = Some(arg),
+            None => return Err(format!("unexpected argument: {}", arg)),
         }
+    }
+    Ok((Params::new(params), path))
+}
+
+fn run_one(problem: &str, args: &[String]) -> Result<(), String> {
+    let (day, part) = parse_problem(problem)?;
+    let (params, path) = parse_params(args)?;
+    let input = match path {
+        Some(path) => aoc::Input::from_path(path).map_err(|e| format!("{}: {}", path, e))?,
+        None => aoc::read_input().map_err(|e| format!("stdin: {}", e))?,
     };
+    let start = Instant::now();
+    let solution = day
+        .solve_with(part, input, &params)
+        .map_err(|e| format!("day {} part {}: {}", day.number, part, e))?;
+    println!("{}", solution);
+    println!("took: {:#?}", Instant::now().duration_since(start));
+    Ok(())
 }
 
-generate_runner!(
-    (1, one),
-    (2, two),
-    (3, three),
-    (4, four),
-    (5, five),
-    (6, six),
-    (7, seven),
-    (8, eight),
-    (9, nine),
-    (10, ten),
-    (11, eleven),
-    (12, twelve),
-    (13, thirteen)
-);
+struct Row {
+    day: u8,
+    part: String,
+    answer: String,
+    took: Option<Duration>,
+}
+
+/// Solves every part of `days` from its input file and prints a table of the
+/// answers and how long each took.
+fn run_table(days: RangeInclusive<u8>) -> Result<(), String> {
+    let mut rows = vec![];
+    let mut failed = false;
+    for day in solver::days().iter().filter(|d| days.contains(&d.number)) {
+        let path = aoc::input_path(day.number);
+        let Ok(input) = fs::read_to_string(&path) else {
+            rows.push(Row {
+                day: day.number,
+                part: "-".to_string(),
+                answer: format!("missing {}", path.display()),
+                took: None,
+            });
+            continue;
+        };
+        for part in Part::ALL {
+            let start = Instant::now();
+            let answer = day.solve(part, aoc::Input::from(input.as_str()));
+            let took = Instant::now().duration_since(start);
+            let answer = answer.map_or_else(
+                |e| {
+                    failed = true;
+                    format!("error: {}", e)
+                },
+                |a| a.to_string(),
+            );
+            rows.push(Row {
+                day: day.number,
+                part: part.to_string(),
+                answer,
+                took: Some(took),
+            });
+        }
+    }
+    print_table(&rows);
+    if failed {
+        return Err("some parts failed".to_string());
+    }
+    Ok(())
+}
+
+fn print_table(rows: &[Row]) {
+    let width = rows
+        .iter()
+        .map(|r| r.answer.len())
+        .chain(["answer".len()])
+        .max()
+        .unwrap_or_default();
+    let total: Duration = rows.iter().filter_map(|r| r.took).sum();
+    println!("day  part  {:<width$}  {:>10}", "answer", "time");
+    for row in rows {
+        let took = row.took.map_or_else(String::new, |t| format!("{:.2?}", t));
+        println!(
+            "{:>3}  {:>4}  {:<width$}  {:>10}",
+            row.day, row.part, row.answer, took
+        );
+    }
+    println!(
+        "{:<w$}  {:>10}",
+        "total",
+        format!("{:.2?}", total),
+        w = width + 11
+    );
+}
+
+/// Checks each part of `days` against its recorded answer, failing if any
+/// part gives a different one.
+fn run_verify(days: RangeInclusive<u8>) -> Result<(), String> {
+    let mut failed = 0;
+    for day in solver::days().iter().filter(|d| days.contains(&d.number)) {
+        let answers = Answers::load(day.number)
+            .map_err(|e| format!("{}: {}", answers::answers_path(day.number).display(), e))?;
+        let input_path = aoc::input_path(day.number);
+        let input = fs::read_to_string(&input_path).ok();
+        for part in Part::ALL {
+            let status = match (&input, answers.as_ref().and_then(|a| a.get(part))) {
+                (None, _) => format!("MISSING no input at {}", input_path.display()),
+                (_, None) => format!(
+                    "MISSING no answer in {}",
+                    answers::answers_path(day.number).display()
+                ),
+                (Some(input), Some(expected)) => {
+                    match day.solve(part, aoc::Input::from(input.as_str())) {
+                        Ok(got) if got.to_string() == expected => format!("PASS {}", expected),
+                        Ok(got) => {
+                            failed += 1;
+                            format!("FAIL expected {}, got {}", expected, got)
+                        }
+                        Err(e) => {
+                            failed += 1;
+                            format!("FAIL {}", e)
+                        }
+                    }
+                }
+            };
+            println!("day {:>2} part {}: {}", day.number, part, status);
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} part(s) failed verification", failed));
+    }
+    Ok(())
+}
 
 fn main() {
     let args: Vec<_> = args().collect();
-    let Some(problem) = args.get(1) else {
-        eprintln!("expected a problem number");
-        process::exit(1);
+    let result = match args.get(1).map(String::as_str) {
+        None => Err(format!("expected a problem number\n{}", USAGE)),
+        Some("all") => run_table(0..=u8::MAX),
+        Some("verify") => match args.get(2) {
+            None => run_verify(0..=u8::MAX),
+            Some(day) => day
+                .parse()
+                .map_err(|_| format!("invalid day: {}", day))
+                .and_then(|day| match solver::day(day) {
+                    Some(_) => run_verify(day..=day),
+                    None => Err(format!("no solver for day {}", day)),
+                }),
+        },
+        Some(days) if days.contains("..") => parse_days(days).and_then(run_table),
+        Some(problem) => run_one(problem, &args[2..]),
     };
-    let input = aoc::read_input();
-    let start = Instant::now();
-    match run(problem, input) {
-        Ok(solution) => {
-            println!("{}", solution);
-            println!("took: {:#?}", Instant::now().duration_since(start));
-        }
-        Err(e) => eprintln!("error: {}", e),
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
     }
 }
@@ -1,63 +1,275 @@
-#[derive(Clone, Copy)]
-enum NextValue {
-    First,
-    Last,
+use std::ops::Neg;
+
+use num::{BigInt, BigRational, CheckedAdd, CheckedMul, CheckedSub, One, Zero};
+
+use crate::ParseError;
+
+/// A polynomial with exact rational coefficients, lowest degree first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    coefficients: Vec<BigRational>,
 }
 
-pub fn solve1(input: crate::Input) -> i64 {
-    solve(input, NextValue::Last)
+impl Polynomial {
+    /// The polynomial of lowest degree taking `values[i]` at each `i`, found
+    /// from the first entry of each row of the difference table with Newton's
+    /// forward difference formula, `p(x) = sum of D^k(0) * C(x, k)`.
+    pub fn interpolate(values: &[i64]) -> Polynomial {
+        let mut row = values.iter().map(|&v| BigInt::from(v)).collect::<Vec<_>>();
+        let mut coefficients: Vec<BigRational> = vec![];
+        // C(x, k) as a polynomial in x, starting from C(x, 0) = 1.
+        let mut binomial = vec![BigRational::one()];
+        for k in 0.. {
+            let Some(first) = row.first() else {
+                break;
+            };
+            coefficients.resize(binomial.len(), BigRational::zero());
+            let first = BigRational::from(first.clone());
+            for (c, b) in coefficients.iter_mut().zip(&binomial) {
+                *c += &first * b;
+            }
+            // C(x, k + 1) = C(x, k) * (x - k) / (k + 1)
+            let k = BigRational::from(BigInt::from(k));
+            let mut next = vec![BigRational::zero(); binomial.len() + 1];
+            for (i, b) in binomial.iter().enumerate() {
+                next[i + 1] += b;
+                next[i] -= b * &k;
+            }
+            let k1 = k + BigRational::one();
+            binomial = next.into_iter().map(|b| b / &k1).collect();
+            row = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+        }
+        while coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    /// As [`Polynomial::interpolate`], but only if some row of the difference
+    /// table comes to all zeros. Otherwise any `n` values fit a polynomial of
+    /// degree `n - 1`, and the sequence is too short to show it is one.
+    pub fn fit(values: &[i64]) -> Option<Polynomial> {
+        let p = Polynomial::interpolate(values);
+        let shown = match p.degree() {
+            Some(degree) => degree + 1 < values.len(),
+            None => !values.is_empty(),
+        };
+        shown.then_some(p)
+    }
+
+    /// The highest power of `x` with a coefficient other than zero, or `None`
+    /// for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn eval(&self, x: impl Into<BigInt>) -> BigRational {
+        let x = BigRational::from(x.into());
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, c| acc * &x + c)
+    }
 }
 
-pub fn solve2(input: crate::Input) -> i64 {
-    solve(input, NextValue::First)
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 9;
+    const TITLE: &'static str = "Mirage Maintenance";
+
+    type Parsed = Vec<Vec<i64>>;
+    type Answer1 = BigInt;
+    type Answer2 = BigInt;
+
+    fn parse(input: crate::Input) -> crate::Result<Vec<Vec<i64>>> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(histories: &Vec<Vec<i64>>) -> crate::Result<BigInt> {
+        Ok(part1(histories))
+    }
+
+    fn part2(histories: &Vec<Vec<i64>>) -> crate::Result<BigInt> {
+        Ok(part2(histories))
+    }
+}
+
+pub fn solve1(input: crate::Input) -> crate::Result<BigInt> {
+    Ok(part1(&parse_input(input)?))
 }
 
-fn solve(input: crate::Input, nv: NextValue) -> i64 {
-    parse_input(input).map(|v| predict_next_value(v, nv)).sum()
+/// Sums the value after the last of each history.
+fn part1(histories: &[Vec<i64>]) -> BigInt {
+    solve(histories, |(_, after)| after)
 }
 
-fn predict_next_value(v: Vec<i64>, nv: NextValue) -> i64 {
-    let mut vals = vec![];
-    let mut prev = v;
-    loop {
-        let (diffs, val) = compute_diffs(prev, nv);
-        vals.push(val);
-        if diffs.iter().all(|n| *n == 0) {
-            break;
+pub fn solve2(input: crate::Input) -> crate::Result<BigInt> {
+    Ok(part2(&parse_input(input)?))
+}
+
+/// Sums the value before the first of each history.
+fn part2(histories: &[Vec<i64>]) -> BigInt {
+    solve(histories, |(before, _)| before)
+}
+
+/// Sums one of the values [`extrapolate`] gives for each history, picked by
+/// `pick`. Most histories fit in `i128`; the rest are redone as `BigInt`.
+fn solve(histories: &[Vec<i64>], pick: impl Fn((BigInt, BigInt)) -> BigInt) -> BigInt {
+    histories
+        .iter()
+        .map(|h| {
+            let values = extrapolate::<i128>(h)
+                .map(|(before, after)| (before.into(), after.into()))
+                .or_else(|| extrapolate::<BigInt>(h))
+                .expect("a BigInt does not overflow");
+            pick(values)
+        })
+        .sum()
+}
+
+/// The values of the [`Polynomial`] through `history` just before and just
+/// after it, without building it: the alternating sum of the first entries
+/// of the rows of the difference table, and the sum of their last entries.
+/// `None` if some entry does not fit a `T`.
+fn extrapolate<T>(history: &[i64]) -> Option<(T, T)>
+where
+    T: From<i64> + Zero + One + Neg<Output = T> + CheckedAdd + CheckedSub + CheckedMul,
+{
+    let mut row = history.iter().map(|&v| T::from(v)).collect::<Vec<_>>();
+    let (mut before, mut after) = (T::zero(), T::zero());
+    let mut sign = T::one();
+    while row.iter().any(|v| !v.is_zero()) {
+        before = before.checked_add(&row[0].checked_mul(&sign)?)?;
+        after = after.checked_add(row.last()?)?;
+        sign = -sign;
+        row = row
+            .windows(2)
+            .map(|w| w[1].checked_sub(&w[0]))
+            .collect::<Option<_>>()?;
+    }
+    Some((before, after))
+}
+
+fn parse_input(input: crate::Input) -> Result<Vec<Vec<i64>>, ParseError> {
+    input
+        .numbered()
+        .map(|(n, line)| {
+            let v = line
+                .split_ascii_whitespace()
+                .map(|s| crate::parse(n, &line, s))
+                .collect::<Result<Vec<_>, _>>()?;
+            if v.is_empty() {
+                return Err(ParseError::new(
+                    n,
+                    1,
+                    line,
+                    "expected a sequence of numbers",
+                ));
+            }
+            Ok(v)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{BigInt, BigRational};
+
+    use super::{extrapolate, solve1, solve2, Polynomial};
+
+    const EXAMPLE: &str = "\
+0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 114.into());
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 2.into());
+    }
+
+    #[test]
+    fn test_constant_and_negative_sequences() {
+        assert_eq!(solve1("7 7 7".into()).unwrap(), 7.into());
+        assert_eq!(solve1("-2 -4 -6 -8".into()).unwrap(), (-10).into());
+        assert_eq!(solve2("-2 -4 -6 -8".into()).unwrap(), 0.into());
+    }
+
+    #[test]
+    fn test_polynomial_degree() {
+        assert_eq!(
+            Polynomial::fit(&[0, 3, 6, 9, 12, 15]).unwrap().degree(),
+            Some(1)
+        );
+        assert_eq!(
+            Polynomial::fit(&[1, 3, 6, 10, 15, 21]).unwrap().degree(),
+            Some(2)
+        );
+        assert_eq!(Polynomial::fit(&[0, 0]).unwrap().degree(), None);
+        assert_eq!(Polynomial::fit(&[1, 2, 4, 8, 16]), None);
+        assert_eq!(Polynomial::fit(&[5]), None);
+        assert_eq!(Polynomial::interpolate(&[1, 2, 4, 8, 16]).degree(), Some(4));
+    }
+
+    #[test]
+    fn test_polynomial_eval_far_away() {
+        let triangle = Polynomial::fit(&[0, 1, 3, 6, 10]).unwrap();
+        assert_eq!(triangle.eval(-1), BigRational::from(BigInt::from(0)));
+        assert_eq!(triangle.eval(-4), BigRational::from(BigInt::from(6)));
+        let cubes = Polynomial::fit(&[0, 1, 8, 27, 64]).unwrap();
+        let x = BigInt::from(10).pow(20);
+        assert_eq!(cubes.eval(x.clone()), BigRational::from(x.pow(3)));
+    }
+
+    #[test]
+    fn test_extrapolate_matches_polynomial() {
+        let histories: [&[i64]; 5] = [
+            &[0, 3, 6, 9, 12, 15],
+            &[10, 13, 16, 21, 30, 45],
+            &[1, 2, 4, 8, 16],
+            &[-7, 0, 19, -3, 5, 5, 1000, -2],
+            &[42],
+        ];
+        for h in histories {
+            let p = Polynomial::interpolate(h);
+            let (before, after) = extrapolate::<i128>(h).unwrap();
+            assert_eq!(
+                BigRational::from(BigInt::from(before)),
+                p.eval(-1),
+                "{:?}",
+                h
+            );
+            assert_eq!(
+                BigRational::from(BigInt::from(after)),
+                p.eval(h.len() as i64),
+                "{:?}",
+                h
+            );
         }
-        prev = diffs;
-    }
-    extrapolate(vals.into_iter().rev(), nv)
-}
-
-fn compute_diffs(v: Vec<i64>, nv: NextValue) -> (Vec<i64>, i64) {
-    let (first, v) = v.split_first().unwrap();
-    let (diffs, last) = v.iter().cloned().fold(
-        (Vec::with_capacity(v.len()), *first),
-        |(mut diffs, a), b| {
-            diffs.push(b - a);
-            (diffs, b)
-        },
-    );
-    let n = match nv {
-        NextValue::First => *first,
-        NextValue::Last => last,
-    };
-    (diffs, n)
-}
-
-fn extrapolate(vs: impl Iterator<Item = i64>, nv: NextValue) -> i64 {
-    let op = match nv {
-        NextValue::First => std::ops::Sub::<i64>::sub,
-        NextValue::Last => std::ops::Add::<i64>::add,
-    };
-    [0].into_iter().chain(vs).reduce(|a, b| op(b, a)).unwrap()
-}
-
-fn parse_input(input: crate::Input) -> impl Iterator<Item = Vec<i64>> {
-    input.map(|line| {
-        line.split_ascii_whitespace()
-            .filter_map(|s| s.parse().ok())
+    }
+
+    #[test]
+    fn test_differences_beyond_i128() {
+        // Each row of differences doubles, so by the 64th they overflow.
+        let h = (0..65)
+            .map(|i| if i % 2 == 0 { i64::MAX } else { i64::MIN })
+            .collect::<Vec<_>>();
+        assert_eq!(extrapolate::<i128>(&h), None);
+        let p = Polynomial::interpolate(&h);
+        let (before, after) = extrapolate::<BigInt>(&h).unwrap();
+        assert_eq!(BigRational::from(before.clone()), p.eval(-1));
+        assert_eq!(BigRational::from(after.clone()), p.eval(65));
+        let input = h
+            .iter()
+            .map(|v| v.to_string())
             .collect::<Vec<_>>()
-    })
+            .join(" ");
+        assert_eq!(solve1(input.as_str().into()).unwrap(), after);
+        assert_eq!(solve2(input.as_str().into()).unwrap(), before);
+    }
 }
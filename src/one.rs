@@ -1,60 +1,189 @@
-use std::str::Chars;
+use std::collections::{HashMap, VecDeque};
 
-pub fn solve1(input: crate::Input) -> u64 {
-    input
-        .map(|line| {
+use crate::ParseError;
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "Trebuchet?!";
+
+    type Parsed = Vec<String>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: crate::Input) -> crate::Result<Vec<String>> {
+        Ok(input.collect())
+    }
+
+    fn part1(lines: &Vec<String>) -> crate::Result<u64> {
+        part1(lines)
+    }
+
+    fn part2(lines: &Vec<String>) -> crate::Result<u64> {
+        part2(lines)
+    }
+}
+
+pub fn solve1(input: crate::Input) -> crate::Result<u64> {
+    part1(&input.collect::<Vec<_>>())
+}
+
+fn part1(lines: &[String]) -> crate::Result<u64> {
+    let sum = (1..)
+        .zip(lines)
+        .map(|(n, line)| {
             let digits = line
                 .chars()
                 .filter_map(|c| c.to_digit(10).map(|d| d as u64));
-            combine_digits(digits)
+            combine_digits(n, line, digits)
         })
-        .sum()
+        .sum::<Result<_, _>>()?;
+    Ok(sum)
 }
 
-fn combine_digits(mut digits: impl Iterator<Item = u64>) -> u64 {
-    let first = digits.nth(0).unwrap();
+fn combine_digits(
+    n: usize,
+    line: &str,
+    mut digits: impl Iterator<Item = u64>,
+) -> Result<u64, ParseError> {
+    let first = digits
+        .next()
+        .ok_or_else(|| ParseError::at(n, line, line, "no digits in line"))?;
     let last = digits.last().unwrap_or(first);
-    first * 10 + last
+    Ok(first * 10 + last)
 }
 
-pub fn solve2(input: crate::Input) -> u64 {
-    input
-        .map(|line| {
-            let digits = parse_line(&line);
-            combine_digits(digits)
-        })
-        .sum()
+pub fn solve2(input: crate::Input) -> crate::Result<u64> {
+    part2(&input.collect::<Vec<_>>())
 }
 
-fn parse_line(line: &str) -> impl Iterator<Item = u64> + '_ {
-    (0..line.len()).filter_map(|i| parse_digit(line[i..].chars()))
+fn part2(lines: &[String]) -> crate::Result<u64> {
+    let vocabulary = DigitVocabulary::english();
+    let sum = (1..)
+        .zip(lines)
+        .map(|(n, line)| {
+            let digits = vocabulary.digits(line);
+            combine_digits(n, line, digits.into_iter())
+        })
+        .sum::<Result<_, _>>()?;
+    Ok(sum)
 }
 
-fn parse_digit(mut s: Chars) -> Option<u64> {
-    let Some(c) = s.next() else {
-        return None;
-    };
-    match c {
-        'o' => check_suffix(s, ("ne", 1)),
-        't' => check_suffix2(s, ("wo", 2), ("hree", 3)),
-        'f' => check_suffix2(s, ("our", 4), ("ive", 5)),
-        's' => check_suffix2(s, ("ix", 6), ("even", 7)),
-        'e' => check_suffix(s, ("ight", 8)),
-        'n' => check_suffix(s, ("ine", 9)),
-        'z' => check_suffix(s, ("ero", 0)),
-        _ => c.to_digit(10).map(|n| n as u64),
-    }
+/// Words that stand for digits, found wherever they start in a line, even
+/// when they overlap as in "twone". Where several words start at the same
+/// place, the longest one counts.
+///
+/// The words are matched together in one pass with an Aho–Corasick automaton:
+/// a trie of the words, where each node also links to the node for the
+/// longest proper suffix of its text that is in the trie, so that a mismatch
+/// never needs to look at earlier characters again.
+pub struct DigitVocabulary {
+    nodes: Vec<TrieNode>,
 }
 
-fn check_suffix(s: Chars, (suffix, value): (&str, u64)) -> Option<u64> {
-    s.take(suffix.len())
-        .cmp(suffix.chars())
-        .is_eq()
-        .then_some(value)
+#[derive(Default)]
+struct TrieNode {
+    next: HashMap<u8, usize>,
+    /// Where to carry on matching when no edge fits.
+    fail: usize,
+    /// The length and value of the word ending here, if any.
+    word: Option<(usize, u64)>,
+    /// The nearest node along the `fail` links where a word ends.
+    output: Option<usize>,
 }
 
-fn check_suffix2(s: Chars, p1: (&str, u64), p2: (&str, u64)) -> Option<u64> {
-    check_suffix(s.clone(), p1).or_else(|| check_suffix(s, p2))
+impl DigitVocabulary {
+    pub fn new<'a>(words: impl IntoIterator<Item = (&'a str, u64)>) -> DigitVocabulary {
+        let mut nodes = vec![TrieNode::default()];
+        for (word, value) in words.into_iter().filter(|(w, _)| !w.is_empty()) {
+            let mut node = 0;
+            for &b in word.as_bytes() {
+                node = match nodes[node].next.get(&b) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(TrieNode::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].next.insert(b, next);
+                        next
+                    }
+                };
+            }
+            nodes[node].word = Some((word.len(), value));
+        }
+        // Breadth first, so a node's fail link is done before its children's.
+        let mut queue = VecDeque::from_iter(nodes[0].next.values().copied());
+        while let Some(node) = queue.pop_front() {
+            let edges = nodes[node]
+                .next
+                .iter()
+                .map(|(&b, &child)| (b, child))
+                .collect::<Vec<_>>();
+            for (b, child) in edges {
+                let mut fail = nodes[node].fail;
+                let fail = loop {
+                    match nodes[fail].next.get(&b) {
+                        Some(&to) => break to,
+                        None if fail == 0 => break 0,
+                        None => fail = nodes[fail].fail,
+                    }
+                };
+                nodes[child].fail = fail;
+                nodes[child].output = match nodes[fail].word {
+                    Some(_) => Some(fail),
+                    None => nodes[fail].output,
+                };
+                queue.push_back(child);
+            }
+        }
+        DigitVocabulary { nodes }
+    }
+
+    /// The digits `0` to `9` and the English words for them.
+    pub fn english() -> DigitVocabulary {
+        const WORDS: [&str; 10] = [
+            "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ];
+        const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        DigitVocabulary::new(
+            (0..)
+                .zip(WORDS)
+                .chain((0..).zip(DIGITS))
+                .map(|(v, w)| (w, v)),
+        )
+    }
+
+    /// The values of the words in `line`, in the order they start.
+    pub fn digits(&self, line: &str) -> Vec<u64> {
+        // The longest word starting at each byte of the line.
+        let mut starts: Vec<Option<(usize, u64)>> = vec![None; line.len()];
+        let mut node = 0;
+        for (end, b) in (1..).zip(line.bytes()) {
+            node = loop {
+                match self.nodes[node].next.get(&b) {
+                    Some(&next) => break next,
+                    None if node == 0 => break 0,
+                    None => node = self.nodes[node].fail,
+                }
+            };
+            let mut found = Some(node)
+                .filter(|&n| self.nodes[n].word.is_some())
+                .or(self.nodes[node].output);
+            while let Some(n) = found {
+                let (len, value) = self.nodes[n].word.expect("output links end at words");
+                let start = &mut starts[end - len];
+                if start.is_none_or(|(l, _)| l < len) {
+                    *start = Some((len, value));
+                }
+                found = self.nodes[n].output;
+            }
+        }
+        starts
+            .into_iter()
+            .flatten()
+            .map(|(_, value)| value)
+            .collect()
+    }
 }
 
 #[allow(dead_code)]
@@ -75,7 +204,7 @@ fn parse_digit_slow(d: &str) -> Option<u64> {
         };
         Some(d)
     };
-    d.parse::<u64>().ok().or_else(|| p())
+    d.parse::<u64>().ok().or_else(p)
 }
 
 #[allow(dead_code)]
@@ -95,3 +224,81 @@ fn parse_line_slow(line: &str) -> impl Iterator<Item = u64> {
     // dbg!(line, &digits);
     digits.into_iter()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_line_slow, solve1, solve2, DigitVocabulary};
+
+    fn parse_line(line: &str) -> Vec<u64> {
+        DigitVocabulary::english().digits(line)
+    }
+
+    #[test]
+    fn test_solve1_example() {
+        let input = "\
+1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+        assert_eq!(solve1(input.into()).unwrap(), 142);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        let input = "\
+two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+        assert_eq!(solve2(input.into()).unwrap(), 281);
+    }
+
+    #[test]
+    fn test_parse_line_overlapping_words() {
+        assert_eq!(parse_line("twone"), [2, 1]);
+        assert_eq!(parse_line("eightwo"), [8, 2]);
+        assert_eq!(parse_line("oneight"), [1, 8]);
+        assert_eq!(parse_line("sevenine2"), [7, 9, 2]);
+        assert_eq!(solve2("twone".into()).unwrap(), 21);
+    }
+
+    #[test]
+    fn test_parse_line_matches_slow() {
+        for line in [
+            "xtwone3four",
+            "zoneight234",
+            "threeeight",
+            "nineninenine",
+            "fivezg8jmf6",
+        ] {
+            assert_eq!(
+                parse_line(line),
+                parse_line_slow(line).collect::<Vec<_>>(),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_line_without_digits() {
+        let e = solve1("a1\nabc".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 2, column 1: no digits in line: \"abc\""
+        );
+    }
+
+    #[test]
+    fn test_custom_vocabulary() {
+        let french = DigitVocabulary::new([("un", 1), ("deux", 2), ("trois", 3), ("huit", 8)]);
+        assert_eq!(french.digits("troisdeuxhuitun"), [3, 2, 8, 1]);
+        let teens = DigitVocabulary::new([("seven", 7), ("seventeen", 17), ("teen", 99)]);
+        assert_eq!(teens.digits("xseventeenx"), [17, 99]);
+        let nested = DigitVocabulary::new([("abcd", 1), ("bc", 2), ("c", 3)]);
+        assert_eq!(nested.digits("abcd"), [1, 2, 3]);
+    }
+}
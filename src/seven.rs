@@ -1,3 +1,5 @@
+use crate::ParseError;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum HandStrength {
     HighCard,
@@ -55,7 +57,7 @@ impl HandStrength {
     }
 }
 
-trait Card: Ord + Clone + From<char> {
+trait Card: Ord + Clone + From<base::Card> {
     fn hand_strength(cards: Vec<Self>) -> HandStrength;
 }
 
@@ -76,17 +78,44 @@ impl<C: Card> Hand<C> {
     }
 }
 
-pub fn solve1(input: crate::Input) -> u64 {
-    solve::<CardOne>(input)
+/// Each hand's cards and bid, as written; the parts read the cards differently.
+pub struct Hands(Vec<(Vec<base::Card>, u64)>);
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 7;
+    const TITLE: &'static str = "Camel Cards";
+
+    type Parsed = Hands;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: crate::Input) -> crate::Result<Hands> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(hands: &Hands) -> crate::Result<u64> {
+        Ok(winnings::<CardOne>(hands))
+    }
+
+    fn part2(hands: &Hands) -> crate::Result<u64> {
+        Ok(winnings::<CardTwo>(hands))
+    }
 }
 
-pub fn solve2(input: crate::Input) -> u64 {
-    solve::<CardTwo>(input)
+pub fn solve1(input: crate::Input) -> crate::Result<u64> {
+    Ok(winnings::<CardOne>(&parse_input(input)?))
 }
 
-fn solve<C: Card>(input: crate::Input) -> u64 {
-    let mut hands = parse_input::<C>(input)
-        .map(|(cards, bid)| Hand::of(cards, bid))
+pub fn solve2(input: crate::Input) -> crate::Result<u64> {
+    Ok(winnings::<CardTwo>(&parse_input(input)?))
+}
+
+fn winnings<C: Card>(Hands(hands): &Hands) -> u64 {
+    let mut hands = hands
+        .iter()
+        .map(|(cards, bid)| Hand::of(cards.iter().copied().map(C::from).collect(), *bid))
         .collect::<Vec<_>>();
     hands.sort();
     hands
@@ -96,21 +125,44 @@ fn solve<C: Card>(input: crate::Input) -> u64 {
         .sum()
 }
 
-fn parse_input<C: Card>(input: crate::Input) -> impl Iterator<Item = (Vec<C>, u64)> {
-    input.map(|line| {
-        let (cards, bid) = line.split_once(" ").unwrap();
-        let cards = cards.chars().map(|c| C::from(c)).collect();
-        let bid = bid.parse().unwrap();
-        (cards, bid)
-    })
+const HAND_SIZE: usize = 5;
+
+fn parse_input(input: crate::Input) -> Result<Hands, ParseError> {
+    fn parse_line(n: usize, line: &str) -> Result<(Vec<base::Card>, u64), ParseError> {
+        let (cards, bid) = line
+            .split_once(' ')
+            .ok_or_else(|| ParseError::at(n, line, line, "expected '<cards> <bid>'"))?;
+        let cards = cards
+            .char_indices()
+            .map(|(i, c)| {
+                base::Card::try_from(c).map_err(|_| {
+                    ParseError::at(n, line, &cards[i..i + c.len_utf8()], "unknown card")
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if cards.len() != HAND_SIZE {
+            return Err(ParseError::at(
+                n,
+                line,
+                &line[..line.len() - bid.len() - 1],
+                "expected 5 cards",
+            ));
+        }
+        Ok((cards, crate::parse(n, line, bid)?))
+    }
+    input
+        .numbered()
+        .map(|(n, line)| parse_line(n, &line))
+        .collect::<Result<_, _>>()
+        .map(Hands)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct CardOne(base::Card);
 
-impl From<char> for CardOne {
-    fn from(value: char) -> Self {
-        Self(base::Card::from(value))
+impl From<base::Card> for CardOne {
+    fn from(value: base::Card) -> Self {
+        Self(value)
     }
 }
 
@@ -144,10 +196,9 @@ impl Card for CardOne {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct CardTwo(base::Card);
 
-impl From<char> for CardTwo {
-    fn from(value: char) -> Self {
+impl From<base::Card> for CardTwo {
+    fn from(c: base::Card) -> Self {
         use base::Card::{Jack, Joker};
-        let c = base::Card::from(value);
         Self(if c == Jack { Joker } else { c })
     }
 }
@@ -205,10 +256,12 @@ mod base {
         Ace,
     }
 
-    impl From<char> for Card {
-        fn from(value: char) -> Self {
+    impl TryFrom<char> for Card {
+        type Error = char;
+
+        fn try_from(value: char) -> Result<Self, Self::Error> {
             use Card::*;
-            match value {
+            Ok(match value {
                 '2' => Two,
                 '3' => Three,
                 '4' => Four,
@@ -222,8 +275,84 @@ mod base {
                 'Q' => Queen,
                 'K' => King,
                 'A' => Ace,
-                c @ _ => unreachable!("{}", c),
-            }
+                c => return Err(c),
+            })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{base, solve1, solve2, Card, CardOne, CardTwo, HandStrength};
+
+    const EXAMPLE: &str = "\
+32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    fn cards<C: Card>(hand: &str) -> Vec<C> {
+        hand.chars()
+            .map(|c| C::from(base::Card::try_from(c).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 6440);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 5905);
+    }
+
+    #[test]
+    fn test_card_one_strengths() {
+        use HandStrength::*;
+        for (hand, strength) in [
+            ("23456", HighCard),
+            ("A23A4", OnePair),
+            ("23432", TwoPair),
+            ("TTT98", ThreeOfAKind),
+            ("23332", FullHouse),
+            ("AA8AA", FourOfAKind),
+            ("AAAAA", FiveOfAKind),
+            ("JJJJJ", FiveOfAKind),
+        ] {
+            assert_eq!(CardOne::hand_strength(cards(hand)), strength, "{}", hand);
+        }
+    }
+
+    #[test]
+    fn test_card_two_jokers() {
+        use HandStrength::*;
+        for (hand, strength) in [
+            ("JJJJJ", FiveOfAKind),
+            ("JJJJ2", FiveOfAKind),
+            ("JJJ23", FourOfAKind),
+            ("JJ234", ThreeOfAKind),
+            ("J2345", OnePair),
+            ("J2233", FullHouse),
+            ("QJJQ2", FourOfAKind),
+        ] {
+            assert_eq!(CardTwo::hand_strength(cards(hand)), strength, "{}", hand);
+        }
+    }
+
+    #[test]
+    fn test_all_jokers_is_weakest_five_of_a_kind() {
+        assert_eq!(solve2("22222 10\nJJJJJ 1".into()).unwrap(), 1 + 2 * 10);
+        assert_eq!(solve1("22222 10\nJJJJJ 1".into()).unwrap(), 10 + 2);
+    }
+
+    #[test]
+    fn test_hand_size() {
+        let e = solve1("32T3 765".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 1, column 1: expected 5 cards: \"32T3\""
+        );
+    }
+}
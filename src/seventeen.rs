@@ -0,0 +1,206 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{
+    grid::{Dir, Grid, Pos},
+    Input, ParseError,
+};
+
+/// The heat lost entering a city block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HeatLoss(u32);
+
+impl TryFrom<char> for HeatLoss {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        c.to_digit(10).map(HeatLoss).ok_or(c)
+    }
+}
+
+/// How far a crucible must and may go in a straight line before it turns.
+/// It must also have gone the least distance straight to stop at the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crucible {
+    pub min_straight: usize,
+    pub max_straight: usize,
+}
+
+impl Crucible {
+    pub const NORMAL: Crucible = Crucible {
+        min_straight: 1,
+        max_straight: 3,
+    };
+
+    pub const ULTRA: Crucible = Crucible {
+        min_straight: 4,
+        max_straight: 10,
+    };
+}
+
+/// The city blocks between the lava pool and the factory.
+pub struct City(Grid<HeatLoss>);
+
+/// Where a crucible is, the way it is heading, and how many blocks it has
+/// come in that direction.
+type State = (Pos, Dir, usize);
+
+impl City {
+    /// The least heat lost taking `crucible` from the top left block to the
+    /// bottom right one, by Dijkstra's algorithm over [`State`]s, since the
+    /// blocks a crucible may enter next depend on how it got where it is.
+    ///
+    /// The least loss found so far for each state is kept in a flat table,
+    /// indexed as by [`City::state_index`].
+    pub fn least_heat_loss(&self, crucible: Crucible) -> crate::Result<u32> {
+        let blocks = &self.0;
+        let end = Pos::new(
+            blocks.height().saturating_sub(1),
+            blocks.width().saturating_sub(1),
+        );
+        if end == Pos::new(0, 0) {
+            // The crucible starts at the factory.
+            return Ok(0);
+        }
+        let states =
+            blocks.width() * blocks.height() * Dir::ALL.len() * (crucible.max_straight + 1);
+        let mut best = vec![u32::MAX; states];
+        let index = |state| self.state_index(crucible, state);
+        let mut queue = BinaryHeap::new();
+        for dir in [Dir::East, Dir::South] {
+            best[index((Pos::new(0, 0), dir, 0))] = 0;
+            queue.push(Reverse((0, (Pos::new(0, 0), dir, 0))));
+        }
+        while let Some(Reverse((loss, state))) = queue.pop() {
+            let (pos, dir, run) = state;
+            if best[index(state)] < loss {
+                continue;
+            }
+            if pos == end && run >= crucible.min_straight {
+                return Ok(loss);
+            }
+            for next_dir in Dir::ALL {
+                let next_run = if next_dir == dir {
+                    run + 1
+                } else if next_dir != dir.opposite() && run >= crucible.min_straight {
+                    1
+                } else {
+                    continue;
+                };
+                if next_run > crucible.max_straight {
+                    continue;
+                }
+                let Some(next) = blocks.step(pos, next_dir) else {
+                    continue;
+                };
+                let next_state = (next, next_dir, next_run);
+                let next_loss = loss + blocks[next].0;
+                if next_loss < best[index(next_state)] {
+                    best[index(next_state)] = next_loss;
+                    queue.push(Reverse((next_loss, next_state)));
+                }
+            }
+        }
+        Err(crate::Error::Unsolvable(
+            "the crucible cannot reach the factory".to_string(),
+        ))
+    }
+
+    /// Where `state` lives in a table with a slot for every block, direction
+    /// and run of up to `crucible.max_straight` blocks.
+    fn state_index(&self, crucible: Crucible, (pos, dir, run): State) -> usize {
+        let block = pos.row * self.0.width() + pos.col;
+        (block * Dir::ALL.len() + dir as usize) * (crucible.max_straight + 1) + run
+    }
+}
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 17;
+    const TITLE: &'static str = "Clumsy Crucible";
+
+    type Parsed = City;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: Input) -> crate::Result<City> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(city: &City) -> crate::Result<u32> {
+        city.least_heat_loss(Crucible::NORMAL)
+    }
+
+    fn part2(city: &City) -> crate::Result<u32> {
+        city.least_heat_loss(Crucible::ULTRA)
+    }
+}
+
+pub fn solve1(input: Input) -> crate::Result<u32> {
+    parse_input(input)?.least_heat_loss(Crucible::NORMAL)
+}
+
+pub fn solve2(input: Input) -> crate::Result<u32> {
+    parse_input(input)?.least_heat_loss(Crucible::ULTRA)
+}
+
+fn parse_input(input: Input) -> Result<City, ParseError> {
+    Grid::parse(input, "expected a digit").map(City)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve1, solve2};
+
+    const EXAMPLE: &str = "\
+2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 102);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 94);
+    }
+
+    #[test]
+    fn test_ultra_must_go_four_before_stopping() {
+        let input = "\
+111111111111
+999999999991
+999999999991
+999999999991
+999999999991";
+        assert_eq!(solve2(input.into()).unwrap(), 71);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        assert_eq!(solve1("111".into()).unwrap(), 2);
+        let e = solve2("111".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "no solution: the crucible cannot reach the factory"
+        );
+    }
+
+    #[test]
+    fn test_start_is_factory() {
+        assert_eq!(solve1("5".into()).unwrap(), 0);
+        assert_eq!(solve2("5".into()).unwrap(), 0);
+    }
+}
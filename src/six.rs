@@ -1,50 +1,189 @@
 use std::iter;
 
-pub fn solve1(input: crate::Input) -> u64 {
-    solve(parse_input(input))
-}
-
-pub fn solve2(input: crate::Input) -> u64 {
-    solve(parse_input2(input))
-}
-
-fn solve(input: impl Iterator<Item = (u64, u64)>) -> u64 {
-    input
-        .map(|(race_t, record_d)| {
-            (0..=race_t)
-                .filter(move |charge_t| {
-                    let v = *charge_t;
-                    let t = race_t - *charge_t;
-                    let d = t * v;
-                    d.gt(&record_d)
-                })
-                .count() as u64
-        })
-        .reduce(|a, n| a * n)
-        .unwrap()
-}
-
-fn parse_input(input: crate::Input) -> impl Iterator<Item = (u64, u64)> {
-    let mut input = input.map(|s| {
-        s.split_once(':')
-            .unwrap()
-            .1
-            .split_ascii_whitespace()
-            .filter_map(|n| n.parse::<u64>().ok())
-            .collect::<Vec<_>>()
+use num::BigUint;
+
+use crate::ParseError;
+
+/// The races on the sheet, read both as separate races and as the one long
+/// race that ignoring the spaces gives.
+pub struct Races {
+    races: Vec<(BigUint, BigUint)>,
+    joined: (BigUint, BigUint),
+}
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Wait For It";
+
+    type Parsed = Races;
+    type Answer1 = BigUint;
+    type Answer2 = BigUint;
+
+    fn parse(input: crate::Input) -> crate::Result<Races> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(races: &Races) -> crate::Result<BigUint> {
+        Ok(solve(races.races.iter()))
+    }
+
+    fn part2(races: &Races) -> crate::Result<BigUint> {
+        Ok(solve([&races.joined].into_iter()))
+    }
+}
+
+pub fn solve1(input: crate::Input) -> crate::Result<BigUint> {
+    Ok(solve(parse_input(input)?.races.iter()))
+}
+
+pub fn solve2(input: crate::Input) -> crate::Result<BigUint> {
+    Ok(solve([&parse_input(input)?.joined].into_iter()))
+}
+
+fn solve<'a>(races: impl Iterator<Item = &'a (BigUint, BigUint)>) -> BigUint {
+    races
+        .map(|(race_t, record_d)| ways_to_win(race_t, record_d))
+        .product()
+}
+
+/// Counts the charge times `t` in `0..=race_t` that travel further than
+/// `record_d`, that is where `t * (race_t - t) > record_d`.
+///
+/// The winning times lie strictly between the roots of
+/// `t^2 - race_t*t + record_d`, symmetric about `race_t / 2`. The integer
+/// square root of the discriminant puts the first guess at or just below
+/// the first winning time, and stepping up from there settles ties with the
+/// record exactly.
+fn ways_to_win(race_t: &BigUint, record_d: &BigUint) -> BigUint {
+    let zero = BigUint::from(0u8);
+    let (t, d) = (race_t, record_d);
+    let square = t * t;
+    let four_d = d * 4u8;
+    if square < four_d {
+        return zero;
+    }
+    let mut lo = (t - (square - four_d).sqrt()) / 2u8;
+    while &lo * (t - &lo) <= *d {
+        lo += 1u8;
+        if &lo * 2u8 > *t {
+            return zero;
+        }
+    }
+    t + 1u8 - lo * 2u8
+}
+
+/// Reads the `Time:` and `Distance:` lines, handing each one's values to
+/// `parse_values`.
+fn parse_lines<T>(
+    lines: &[String],
+    parse_values: impl Fn(usize, &str, &str) -> Result<T, ParseError>,
+) -> Result<(T, T), ParseError> {
+    let mut values = (1..).zip(lines).take(2).map(|(n, line)| {
+        let (_, values) = line
+            .split_once(':')
+            .ok_or_else(|| ParseError::at(n, line, line, "expected '<label>:'"))?;
+        parse_values(n, line, values)
     });
-    iter::zip(input.next().unwrap(), input.next().unwrap())
+    let mut next = |n, label| {
+        values
+            .next()
+            .unwrap_or_else(|| Err(ParseError::new(n, 1, "", label)))
+    };
+    Ok((
+        next(1, "expected a 'Time:' line")?,
+        next(2, "expected a 'Distance:' line")?,
+    ))
 }
 
-fn parse_input2(input: crate::Input) -> impl Iterator<Item = (u64, u64)> {
-    let mut input = input.filter_map(|s| {
-        s.split_once(':')
-            .unwrap()
-            .1
+fn parse_input(input: crate::Input) -> Result<Races, ParseError> {
+    let lines = input.collect::<Vec<_>>();
+    let (times, distances) = parse_lines(&lines, |n, line, values| {
+        values
             .split_ascii_whitespace()
-            .collect::<String>()
-            .parse::<u64>()
-            .ok()
-    });
-    [(input.next().unwrap(), input.next().unwrap())].into_iter()
+            .map(|s| parse_number(n, line, s, s))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    let joined = parse_lines(&lines, |n, line, values| {
+        let digits = values.split_ascii_whitespace().collect::<String>();
+        parse_number(n, line, values, &digits)
+    })?;
+    Ok(Races {
+        races: iter::zip(times, distances).collect(),
+        joined,
+    })
+}
+
+/// Parses `digits`, reporting `span` of `line` if they are not a number.
+fn parse_number(n: usize, line: &str, span: &str, digits: &str) -> Result<BigUint, ParseError> {
+    digits
+        .parse()
+        .map_err(|_| ParseError::at(n, line, span, "expected a whole number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use super::{solve1, solve2, ways_to_win, Solution};
+    use crate::Solver;
+
+    const EXAMPLE: &str = "\
+Time:      7  15   30
+Distance:  9  40  200";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 288u32.into());
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 71503u32.into());
+    }
+
+    #[test]
+    fn test_record_ties_do_not_win() {
+        // Holding for 10ms or 20ms only matches the 200mm record.
+        assert_eq!(
+            solve1("Time: 30\nDistance: 200".into()).unwrap(),
+            9u32.into()
+        );
+        assert_eq!(solve1("Time: 4\nDistance: 4".into()).unwrap(), 0u32.into());
+    }
+
+    #[test]
+    fn test_ways_to_win_matches_brute_force() {
+        for t in 0u64..40 {
+            for d in 0..t * t / 4 + 2 {
+                let brute = (0..=t).filter(|c| c * (t - c) > d).count();
+                assert_eq!(
+                    ways_to_win(&t.into(), &d.into()),
+                    BigUint::from(brute),
+                    "time {} distance {}",
+                    t,
+                    d
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_beyond_u64() {
+        let input = "Time: 1 0000000000 0000000000\nDistance: 1";
+        let t = BigUint::from(10u8).pow(20);
+        assert_eq!(solve2(input.into()).unwrap(), t - 1u8);
+    }
+
+    #[test]
+    fn test_parse_reports_errors() {
+        let e = Solution::parse("Time: 7 x\nDistance: 9 40".into())
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 1, column 9: expected a whole number: \"x\""
+        );
+    }
 }
@@ -0,0 +1,208 @@
+use rayon::prelude::*;
+
+use crate::{
+    grid::{Dir, Grid, Pos},
+    Input, ParseError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Empty,
+    /// `/`
+    ForwardMirror,
+    /// `\`
+    BackMirror,
+    /// `|`
+    VerticalSplitter,
+    /// `-`
+    HorizontalSplitter,
+}
+
+impl TryFrom<char> for Tile {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Tile::Empty),
+            '/' => Ok(Tile::ForwardMirror),
+            '\\' => Ok(Tile::BackMirror),
+            '|' => Ok(Tile::VerticalSplitter),
+            '-' => Ok(Tile::HorizontalSplitter),
+            c => Err(c),
+        }
+    }
+}
+
+impl Tile {
+    /// The directions a beam heading `dir` leaves this tile in.
+    fn deflect(self, dir: Dir) -> (Dir, Option<Dir>) {
+        use Dir::*;
+        match (self, dir) {
+            (Tile::ForwardMirror, North) => (East, None),
+            (Tile::ForwardMirror, East) => (North, None),
+            (Tile::ForwardMirror, South) => (West, None),
+            (Tile::ForwardMirror, West) => (South, None),
+            (Tile::BackMirror, North) => (West, None),
+            (Tile::BackMirror, East) => (South, None),
+            (Tile::BackMirror, South) => (East, None),
+            (Tile::BackMirror, West) => (North, None),
+            (Tile::VerticalSplitter, East | West) => (North, Some(South)),
+            (Tile::HorizontalSplitter, North | South) => (East, Some(West)),
+            (_, dir) => (dir, None),
+        }
+    }
+}
+
+/// The tiles of the contraption.
+pub struct Contraption(Grid<Tile>);
+
+impl Contraption {
+    /// How many tiles a beam entering at `start` heading `dir` passes
+    /// through, counting every beam the splitters make.
+    ///
+    /// Beams can go round in loops, so each tile remembers the directions
+    /// beams have already left it in, and a beam that would repeat one is
+    /// dropped: everything it would light is already lit.
+    pub fn energized(&self, start: Pos, dir: Dir) -> usize {
+        let tiles = &self.0;
+        if !tiles.contains(start) {
+            return 0;
+        }
+        let mut seen = Grid::new(tiles.width(), tiles.height(), 0u8);
+        let mut beams = vec![(start, dir)];
+        while let Some((pos, dir)) = beams.pop() {
+            let (out, split) = tiles[pos].deflect(dir);
+            for dir in [Some(out), split].into_iter().flatten() {
+                let bit = 1 << dir as u8;
+                if seen[pos] & bit != 0 {
+                    continue;
+                }
+                seen[pos] |= bit;
+                if let Some(next) = tiles.step(pos, dir) {
+                    beams.push((next, dir));
+                }
+            }
+        }
+        seen.iter().filter(|&(_, &dirs)| dirs != 0).count()
+    }
+
+    /// Every place a beam can come in from the edge, with the direction it
+    /// heads.
+    fn entries(&self) -> Vec<(Pos, Dir)> {
+        let (width, height) = (self.0.width(), self.0.height());
+        let cols = (0..width).flat_map(|col| {
+            [
+                (Pos::new(0, col), Dir::South),
+                (Pos::new(height - 1, col), Dir::North),
+            ]
+        });
+        let rows = (0..height).flat_map(|row| {
+            [
+                (Pos::new(row, 0), Dir::East),
+                (Pos::new(row, width - 1), Dir::West),
+            ]
+        });
+        cols.chain(rows).collect()
+    }
+}
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 16;
+    const TITLE: &'static str = "The Floor Will Be Lava";
+
+    type Parsed = Contraption;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: Input) -> crate::Result<Contraption> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(contraption: &Contraption) -> crate::Result<usize> {
+        Ok(part1(contraption))
+    }
+
+    fn part2(contraption: &Contraption) -> crate::Result<usize> {
+        Ok(part2(contraption))
+    }
+}
+
+pub fn solve1(input: Input) -> crate::Result<usize> {
+    Ok(part1(&parse_input(input)?))
+}
+
+fn part1(contraption: &Contraption) -> usize {
+    contraption.energized(Pos::new(0, 0), Dir::East)
+}
+
+pub fn solve2(input: Input) -> crate::Result<usize> {
+    Ok(part2(&parse_input(input)?))
+}
+
+/// Each entry is traced on its own, so they are shared out across threads.
+fn part2(contraption: &Contraption) -> usize {
+    contraption
+        .entries()
+        .into_par_iter()
+        .map(|(start, dir)| contraption.energized(start, dir))
+        .max()
+        .unwrap_or(0)
+}
+
+fn parse_input(input: Input) -> Result<Contraption, ParseError> {
+    Grid::parse(input, "expected '.', '/', '\\', '|' or '-'").map(Contraption)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_input, solve1, solve2};
+    use crate::grid::{Dir, Pos};
+
+    const EXAMPLE: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 46);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 51);
+        let contraption = parse_input(EXAMPLE.into()).unwrap();
+        assert_eq!(contraption.energized(Pos::new(0, 3), Dir::South), 51);
+    }
+
+    #[test]
+    fn test_loop_ends() {
+        // The beam splits at the '-' and goes round the mirrors forever.
+        let input = r"
+.-..\
+.....
+./..-
+.....
+.\../"
+            .trim_start();
+        let contraption = parse_input(input.into()).unwrap();
+        assert_eq!(contraption.energized(Pos::new(0, 1), Dir::South), 16);
+    }
+
+    #[test]
+    fn test_unknown_tile() {
+        let e = solve1(".|\n.x".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            r#"parse error: line 2, column 2: expected '.', '/', '\', '|' or '-': "x""#
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    any::Any,
+    fmt::{self, Display},
+};
+
+use crate::{Error, Input, Result};
+
+/// A single day's puzzle. The input is parsed once, and both parts are solved
+/// from the parsed form, so that the two steps can be timed separately.
+pub trait Solver {
+    const DAY: u8;
+    const TITLE: &'static str;
+
+    type Parsed: 'static;
+    type Answer1: Display + 'static;
+    type Answer2: Display + 'static;
+
+    fn parse(input: Input) -> Result<Self::Parsed>;
+    fn part1(parsed: &Self::Parsed) -> Result<Self::Answer1>;
+    fn part2(parsed: &Self::Parsed) -> Result<Self::Answer2>;
+
+    /// Solves part 1 with settings other than the puzzle's own. Days with
+    /// nothing to set reject any parameter.
+    fn part1_with(parsed: &Self::Parsed, params: &Params) -> Result<Self::Answer1> {
+        params.expect_only(&[])?;
+        Self::part1(parsed)
+    }
+
+    /// As [`Solver::part1_with`], for part 2.
+    fn part2_with(parsed: &Self::Parsed, params: &Params) -> Result<Self::Answer2> {
+        params.expect_only(&[])?;
+        Self::part2(parsed)
+    }
+}
+
+/// Named settings for a part, given on the command line as `--<name> <value>`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Params(Vec<(String, String)>);
+
+impl Params {
+    pub fn new(params: impl IntoIterator<Item = (String, String)>) -> Params {
+        Params(params.into_iter().collect())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Fails on the first parameter whose name is not in `known`.
+    pub fn expect_only(&self, known: &[&str]) -> Result<()> {
+        match self.0.iter().find(|(n, _)| !known.contains(&n.as_str())) {
+            Some((name, _)) => Err(Error::Param(format!("unknown parameter --{}", name))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn from_number(n: u8) -> Option<Part> {
+        match n {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// A day's input as returned by [`Day::parse`], ready to be handed back to
+/// [`Day::solve_parsed`] for the same day.
+pub struct Parsed(Box<dyn Any>);
+
+type ErasedParse = fn(Input) -> Result<Parsed>;
+type ErasedPart = fn(&Parsed, &Params) -> Result<Box<dyn Display>>;
+
+/// A registered [`Solver`] with its types erased, so that every day can be
+/// stored in one table.
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub number: u8,
+    pub title: &'static str,
+    parse: ErasedParse,
+    part1: ErasedPart,
+    part2: ErasedPart,
+}
+
+impl Day {
+    pub const fn of<S: Solver>() -> Day {
+        fn parse<S: Solver>(input: Input) -> Result<Parsed> {
+            Ok(Parsed(Box::new(S::parse(input)?)))
+        }
+        fn parsed<S: Solver>(parsed: &Parsed) -> &S::Parsed {
+            parsed
+                .0
+                .downcast_ref()
+                .expect("input was parsed by a different day")
+        }
+        fn part1<S: Solver>(p: &Parsed, params: &Params) -> Result<Box<dyn Display>> {
+            Ok(Box::new(S::part1_with(parsed::<S>(p), params)?))
+        }
+        fn part2<S: Solver>(p: &Parsed, params: &Params) -> Result<Box<dyn Display>> {
+            Ok(Box::new(S::part2_with(parsed::<S>(p), params)?))
+        }
+        Day {
+            number: S::DAY,
+            title: S::TITLE,
+            parse: parse::<S>,
+            part1: part1::<S>,
+            part2: part2::<S>,
+        }
+    }
+
+    pub fn parse(&self, input: Input) -> Result<Parsed> {
+        (self.parse)(input)
+    }
+
+    /// Solves `part` from input already parsed by this day.
+    ///
+    /// # Panics
+    ///
+    /// If `parsed` came from a different day.
+    pub fn solve_parsed(&self, part: Part, parsed: &Parsed) -> Result<Box<dyn Display>> {
+        self.solve_parsed_with(part, parsed, &Params::default())
+    }
+
+    /// As [`Day::solve_parsed`], with `params` handed to the part.
+    pub fn solve_parsed_with(
+        &self,
+        part: Part,
+        parsed: &Parsed,
+        params: &Params,
+    ) -> Result<Box<dyn Display>> {
+        match part {
+            Part::One => (self.part1)(parsed, params),
+            Part::Two => (self.part2)(parsed, params),
+        }
+    }
+
+    pub fn solve(&self, part: Part, input: Input) -> Result<Box<dyn Display>> {
+        self.solve_parsed(part, &self.parse(input)?)
+    }
+
+    pub fn solve_with(
+        &self,
+        part: Part,
+        input: Input,
+        params: &Params,
+    ) -> Result<Box<dyn Display>> {
+        self.solve_parsed_with(part, &self.parse(input)?, params)
+    }
+}
+
+impl fmt::Debug for Day {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Day")
+            .field("number", &self.number)
+            .field("title", &self.title)
+            .finish_non_exhaustive()
+    }
+}
+
+static DAYS: &[Day] = &[
+    Day::of::<crate::one::Solution>(),
+    Day::of::<crate::two::Solution>(),
+    Day::of::<crate::three::Solution>(),
+    Day::of::<crate::four::Solution>(),
+    Day::of::<crate::five::Solution>(),
+    Day::of::<crate::six::Solution>(),
+    Day::of::<crate::seven::Solution>(),
+    Day::of::<crate::eight::Solution>(),
+    Day::of::<crate::nine::Solution>(),
+    Day::of::<crate::ten::Solution>(),
+    Day::of::<crate::eleven::Solution>(),
+    Day::of::<crate::twelve::Solution>(),
+    Day::of::<crate::thirteen::Solution>(),
+    Day::of::<crate::fourteen::Solution>(),
+    Day::of::<crate::fifteen::Solution>(),
+    Day::of::<crate::sixteen::Solution>(),
+    Day::of::<crate::seventeen::Solution>(),
+    Day::of::<crate::eighteen::Solution>(),
+];
+
+/// Every registered day, in calendar order.
+pub fn days() -> &'static [Day] {
+    DAYS
+}
+
+pub fn day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.number == number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{day, days, Params, Part};
+
+    #[test]
+    fn test_registry_is_ordered_and_unique() {
+        let numbers: Vec<_> = days().iter().map(|d| d.number).collect();
+        assert!(numbers.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            day(5).map(|d| d.title),
+            Some("If You Give A Seed A Fertilizer")
+        );
+        assert!(day(0).is_none());
+    }
+
+    #[test]
+    fn test_unknown_params_are_rejected() {
+        let params = Params::new([("bag".to_string(), "red=1".to_string())]);
+        assert_eq!(params.get("bag"), Some("red=1"));
+        let e = day(1)
+            .unwrap()
+            .solve_with(Part::One, "1".into(), &params)
+            .err()
+            .unwrap();
+        assert_eq!(e.to_string(), "parameter error: unknown parameter --bag");
+    }
+}
@@ -1,10 +1,13 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 
 use petgraph::{algo::dijkstra, graphmap::UnGraphMap};
 
-use crate::Input;
+use crate::{
+    grid::{Dir, Grid, Pos},
+    Input,
+};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Pipe {
     NS,
     EW,
@@ -16,10 +19,12 @@ enum Pipe {
     Start,
 }
 
-impl From<char> for Pipe {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for Pipe {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         use Pipe::*;
-        match c {
+        Ok(match c {
             '|' => NS,
             '-' => EW,
             'L' => NE,
@@ -28,146 +33,242 @@ impl From<char> for Pipe {
             'F' => SE,
             '.' => Ground,
             'S' => Start,
-            _ => unreachable!(),
+            c => return Err(c),
+        })
+    }
+}
+
+impl Pipe {
+    const SHAPES: [Pipe; 6] = [Pipe::NS, Pipe::EW, Pipe::NE, Pipe::NW, Pipe::SW, Pipe::SE];
+
+    /// Whether the pipe leads out of its tile towards `dir`. The start tile
+    /// leads nowhere until [`start_pipe`] has worked out its shape.
+    fn connects(self, dir: Dir) -> bool {
+        use Pipe::*;
+        match self {
+            NS => matches!(dir, Dir::North | Dir::South),
+            EW => matches!(dir, Dir::East | Dir::West),
+            NE => matches!(dir, Dir::North | Dir::East),
+            NW => matches!(dir, Dir::North | Dir::West),
+            SW => matches!(dir, Dir::South | Dir::West),
+            SE => matches!(dir, Dir::South | Dir::East),
+            Ground | Start => false,
         }
     }
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Node {
-    row: isize,
-    col: isize,
+type PipeMaze = UnGraphMap<Pos, ()>;
+
+/// The loop of pipes connected to the start tile, and every tile of the map.
+pub struct Maze {
+    start: Pos,
+    graph: PipeMaze,
+    tiles: Grid<Pipe>,
 }
 
-impl Node {
-    fn north(&self) -> Option<Node> {
-        self.row.gt(&0).then(|| Node {
-            row: self.row - 1,
-            col: self.col,
-        })
-    }
+pub struct Solution;
 
-    fn south(&self) -> Option<Node> {
-        self.row.lt(&139).then(|| Node {
-            row: self.row + 1,
-            col: self.col,
-        })
+impl crate::Solver for Solution {
+    const DAY: u8 = 10;
+    const TITLE: &'static str = "Pipe Maze";
+
+    type Parsed = Maze;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: Input) -> crate::Result<Maze> {
+        build_graph(input)
     }
 
-    fn east(&self) -> Option<Node> {
-        self.col.lt(&139).then(|| Node {
-            row: self.row,
-            col: self.col + 1,
-        })
+    fn part1(maze: &Maze) -> crate::Result<u64> {
+        Ok(part1(maze))
     }
 
-    fn west(&self) -> Option<Node> {
-        self.col.gt(&0).then(|| Node {
-            row: self.row,
-            col: self.col - 1,
-        })
+    fn part2(maze: &Maze) -> crate::Result<u64> {
+        Ok(part2(maze))
     }
 }
 
-type PipeMaze = UnGraphMap<Node, ()>;
+pub fn solve1(input: Input) -> crate::Result<u64> {
+    Ok(part1(&build_graph(input)?))
+}
 
-pub fn solve1(input: Input) -> u64 {
-    let (start, maze, debug) = build_graph(input);
-    let paths = dijkstra(&maze, start, None, |_| 1u64);
-    debug_assert!(paths.keys().all(|n| debug.contains_key(n)));
+fn part1(maze: &Maze) -> u64 {
+    let paths = dijkstra(&maze.graph, maze.start, None, |_| 1u64);
+    debug_assert!(paths.keys().all(|n| maze.tiles.contains(*n)));
     paths.values().copied().max().unwrap()
 }
 
-pub fn solve2(_input: Input) -> u64 {
-    todo!()
+pub fn solve2(input: Input) -> crate::Result<u64> {
+    Ok(part2(&build_graph(input)?))
 }
 
-fn north_edge(m: &HashMap<Node, Pipe>, node: Node) -> Option<Node> {
-    use Pipe::*;
-    node.north().and_then(|north| match m.get(&north).unwrap() {
-        Start | NS | SW | SE => Some(north),
-        _ => None,
-    })
-}
-
-fn south_edge(m: &HashMap<Node, Pipe>, node: Node) -> Option<Node> {
-    use Pipe::*;
-    node.south().and_then(|south| match m.get(&south).unwrap() {
-        Start | NS | NE | NW => Some(south),
-        _ => None,
-    })
+/// Counts the tiles inside the loop by scanning each row from the west and
+/// flipping between outside and inside at every loop pipe that leads north.
+/// A run such as `L--7` crosses the loop once, while `L--J` turns back and
+/// does not cross it at all, which is what lets the loop squeeze between
+/// pipes.
+fn part2(maze: &Maze) -> u64 {
+    let main_loop: HashSet<Pos> = dijkstra(&maze.graph, maze.start, None, |_| 1u64)
+        .into_keys()
+        .collect();
+    let mut enclosed = 0;
+    for row in 0..maze.tiles.height() {
+        let mut inside = false;
+        for col in 0..maze.tiles.width() {
+            let pos = Pos::new(row, col);
+            if !main_loop.contains(&pos) {
+                enclosed += u64::from(inside);
+            } else if maze.tiles[pos].connects(Dir::North) {
+                inside = !inside;
+            }
+        }
+    }
+    enclosed
 }
 
-fn east_edge(m: &HashMap<Node, Pipe>, node: Node) -> Option<Node> {
-    use Pipe::*;
-    node.east().and_then(|east| match m.get(&east).unwrap() {
-        Start | NW | SW | EW => Some(east),
-        _ => None,
-    })
+/// The tile `dir` of `pos`, if its pipe connects back to `pos`.
+fn edge(tiles: &Grid<Pipe>, pos: Pos, dir: Dir) -> Option<Pos> {
+    tiles
+        .step(pos, dir)
+        .filter(|&next| tiles[next].connects(dir.opposite()))
 }
 
-fn west_edge(m: &HashMap<Node, Pipe>, node: Node) -> Option<Node> {
-    use Pipe::*;
-    node.west().and_then(|west| match m.get(&west).unwrap() {
-        Start | NE | SE | EW => Some(west),
-        _ => None,
-    })
-}
-type EdgeFn = fn(&HashMap<Node, Pipe>, Node) -> Option<Node>;
-
-fn make_edges(m: &HashMap<Node, Pipe>, n: Node, one: EdgeFn, two: EdgeFn) -> Vec<(Node, Node)> {
-    [one(m, n), two(m, n)]
-        .into_iter()
-        .filter_map(|n2| n2)
-        .map(|n2| (n, n2))
-        .collect()
+/// Works out which pipe is under the start tile from the neighbours that
+/// connect to it, which must fit exactly one shape.
+fn start_pipe(tiles: &Grid<Pipe>, start: Pos) -> crate::Result<Pipe> {
+    let fits = |pipe: &Pipe| {
+        Dir::ALL
+            .into_iter()
+            .filter(|&d| pipe.connects(d))
+            .all(|d| edge(tiles, start, d).is_some())
+    };
+    let shapes = Pipe::SHAPES.into_iter().filter(fits).collect::<Vec<_>>();
+    match shapes[..] {
+        [pipe] => Ok(pipe),
+        [] => Err(crate::Error::Unsolvable(format!(
+            "no pipe fits the start tile at row {}, column {}",
+            start.row + 1,
+            start.col + 1
+        ))),
+        _ => Err(crate::Error::Unsolvable(format!(
+            "the start tile at row {}, column {} could be any of {:?}",
+            start.row + 1,
+            start.col + 1,
+            shapes
+        ))),
+    }
 }
 
-fn build_graph(input: Input) -> (Node, PipeMaze, HashMap<Node, Pipe>) {
-    let input = parse_input(input);
-    let start = input
+fn build_graph(input: Input) -> crate::Result<Maze> {
+    let mut tiles = Grid::<Pipe>::parse(input, "unknown tile")?;
+    let start = tiles
         .iter()
-        .find_map(|(node, p)| match p {
-            Pipe::Start => Some(*node),
-            _ => None,
-        })
-        .unwrap();
-    let node_to_pipe: HashMap<Node, Pipe> = HashMap::from_iter(input.iter().cloned());
+        .find_map(|(pos, p)| matches!(p, Pipe::Start).then_some(pos))
+        .ok_or_else(|| crate::Error::Unsolvable("no start tile 'S'".to_string()))?;
+    tiles[start] = start_pipe(&tiles, start)?;
+    let t = &tiles;
     let maze = PipeMaze::from_edges(
-        input
+        tiles
             .iter()
-            .copied()
-            .filter_map(|(node, p)| match p {
-                Pipe::NS => Some(make_edges(&node_to_pipe, node, north_edge, south_edge)),
-                Pipe::EW => Some(make_edges(&node_to_pipe, node, east_edge, west_edge)),
-                Pipe::NE => Some(make_edges(&node_to_pipe, node, north_edge, east_edge)),
-                Pipe::NW => Some(make_edges(&node_to_pipe, node, north_edge, west_edge)),
-                Pipe::SW => Some(make_edges(&node_to_pipe, node, south_edge, west_edge)),
-                Pipe::SE => Some(make_edges(&node_to_pipe, node, south_edge, east_edge)),
-                Pipe::Ground | Pipe::Start => None,
-            })
-            .flatten(),
+            .filter(|(_, p)| !matches!(p, Pipe::Ground | Pipe::Start))
+            .flat_map(|(pos, &p)| {
+                Dir::ALL
+                    .into_iter()
+                    .filter(move |&d| p.connects(d))
+                    .filter_map(move |d| edge(t, pos, d))
+                    .map(move |next| (pos, next))
+            }),
     );
-    (start, maze, node_to_pipe)
+    Ok(Maze {
+        start,
+        graph: maze,
+        tiles,
+    })
 }
 
-fn parse_input(input: Input) -> Vec<(Node, Pipe)> {
-    input
-        .enumerate()
-        .flat_map(|(row, l)| {
-            l.bytes()
-                .map(|b| b as char)
-                .enumerate()
-                .map(move |(col, c)| {
-                    (
-                        Node {
-                            row: row as _,
-                            col: col as _,
-                        },
-                        Pipe::from(c),
-                    )
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>()
+#[cfg(test)]
+mod tests {
+    use super::{build_graph, solve1, solve2, Pipe};
+
+    #[test]
+    fn test_solve1_examples() {
+        let input = "\
+-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF";
+        assert_eq!(solve1(input.into()).unwrap(), 4);
+        let input = "\
+7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ";
+        assert_eq!(solve1(input.into()).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_solve2_examples() {
+        let input = "\
+...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+        assert_eq!(solve2(input.into()).unwrap(), 4);
+        let input = "\
+..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+        assert_eq!(solve2(input.into()).unwrap(), 4);
+        let input = "\
+.F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+        assert_eq!(solve2(input.into()).unwrap(), 8);
+        let input = "\
+FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L";
+        assert_eq!(solve2(input.into()).unwrap(), 10);
+    }
+
+    #[test]
+    fn test_start_pipe() {
+        let maze = build_graph("-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF".into()).unwrap();
+        assert_eq!(maze.tiles[maze.start], Pipe::SE);
+        let e = solve1("...\n.S.\n...".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "no solution: no pipe fits the start tile at row 2, column 2"
+        );
+        let e = solve1(".|.\n-S-\n.|.".into()).unwrap_err();
+        assert!(e.to_string().contains("could be any of"), "{}", e);
+    }
 }
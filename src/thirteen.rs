@@ -1,25 +1,72 @@
-use crate::Input;
+use crate::{grid::Grid, Input, ParseError};
 
-type Eq = fn((&String, &String)) -> bool;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Terrain {
+    Ash,
+    Rock,
+}
 
-pub fn solve1(input: Input) -> usize {
-    parse_input(input)
-        .into_iter()
+impl TryFrom<char> for Terrain {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Terrain::Ash),
+            '#' => Ok(Terrain::Rock),
+            c => Err(c),
+        }
+    }
+}
+
+type Line = Vec<Terrain>;
+
+type Eq = fn((&Line, &Line)) -> bool;
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 13;
+    const TITLE: &'static str = "Point of Incidence";
+
+    type Parsed = Vec<Pattern>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: Input) -> crate::Result<Vec<Pattern>> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(patterns: &Vec<Pattern>) -> crate::Result<usize> {
+        Ok(part1(patterns))
+    }
+
+    fn part2(patterns: &Vec<Pattern>) -> crate::Result<usize> {
+        Ok(part2(patterns))
+    }
+}
+
+pub fn solve1(input: Input) -> crate::Result<usize> {
+    Ok(part1(&parse_input(input)?))
+}
+
+fn part1(patterns: &[Pattern]) -> usize {
+    patterns
+        .iter()
         .map(|p| {
             let vert = reflection(&p.cols, eq).unwrap_or(0);
             let hori = reflection(&p.rows, eq).unwrap_or(0);
-            dbg!(vert) + dbg!(hori) * 100
+            vert + hori * 100
         })
         .sum()
 }
 
-fn eq((a, b): (&String, &String)) -> bool {
+fn eq((a, b): (&Line, &Line)) -> bool {
     a == b
 }
 
-fn eq_off_by_one((a, b): (&String, &String)) -> bool {
+fn eq_off_by_one((a, b): (&Line, &Line)) -> bool {
     let mut diff = 0;
-    a.chars().zip(b.chars()).for_each(|(a, b)| {
+    a.iter().zip(b).for_each(|(a, b)| {
         if a != b {
             diff += 1;
         }
@@ -27,10 +74,14 @@ fn eq_off_by_one((a, b): (&String, &String)) -> bool {
     diff <= 1
 }
 
-pub fn solve2(input: Input) -> usize {
-    parse_input(input)
-        .into_iter()
-        .map(|ref p| {
+pub fn solve2(input: Input) -> crate::Result<usize> {
+    Ok(part2(&parse_input(input)?))
+}
+
+fn part2(patterns: &[Pattern]) -> usize {
+    patterns
+        .iter()
+        .map(|p| {
             let vert = reflection(&p.cols, eq_off_by_one)
                 .and_then(|i| reflection(&p.cols, eq).and_then(|j| (i != j).then_some(j)))
                 .unwrap_or(0);
@@ -42,7 +93,7 @@ pub fn solve2(input: Input) -> usize {
         .sum()
 }
 
-fn reflection(v: &[String], eq: Eq) -> Option<usize> {
+fn reflection(v: &[Line], eq: Eq) -> Option<usize> {
     (1..v.len()).find(|i| {
         let (left, right) = v.split_at(*i);
         if left.len() <= right.len() {
@@ -53,34 +104,64 @@ fn reflection(v: &[String], eq: Eq) -> Option<usize> {
     })
 }
 
-#[derive(Default, Debug)]
-struct Pattern {
-    rows: Vec<String>,
-    cols: Vec<String>,
+#[derive(Debug)]
+pub struct Pattern {
+    rows: Vec<Line>,
+    cols: Vec<Line>,
 }
 
-fn parse_input(input: Input) -> Vec<Pattern> {
-    let builders = input.fold(vec![Pattern::default()], |mut v, l| {
-        if l.is_empty() {
-            v.push(Pattern::default());
-            return v;
+impl From<Grid<Terrain>> for Pattern {
+    fn from(grid: Grid<Terrain>) -> Self {
+        Pattern {
+            rows: grid.rows().map(<[_]>::to_vec).collect(),
+            cols: grid.cols().map(|c| c.copied().collect()).collect(),
         }
-        let p = v.last_mut().unwrap();
-        let row = l;
-        if p.cols.len() < row.len() {
-            for _ in 0..(row.len() - p.cols.len()) {
-                p.cols.push(String::new())
-            }
+    }
+}
+
+fn parse_input(input: Input) -> Result<Vec<Pattern>, ParseError> {
+    let mut blocks = vec![vec![]];
+    for (n, row) in input.numbered() {
+        if row.is_empty() {
+            blocks.push(vec![]);
+        } else {
+            blocks.last_mut().unwrap().push((n, row));
         }
-        row.char_indices().for_each(|(col, c)| p.cols[col].push(c));
-        p.rows.push(row);
-        v
-    });
-    builders
+    }
+    blocks
         .into_iter()
-        .map(|b| Pattern {
-            rows: b.rows,
-            cols: b.cols.into_iter().map(|col| col.into()).collect(),
-        })
+        .map(|block| Grid::parse_lines(block, "expected '.' or '#'").map(Pattern::from))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{solve1, solve2};
+
+    const EXAMPLE: &str = "\
+#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.##..##.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 405);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 400);
+    }
+}
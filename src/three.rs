@@ -1,189 +1,187 @@
-use std::{iter::Peekable, ops::RangeInclusive};
+use crate::{
+    grid::{Grid, Pos},
+    ParseError,
+};
 
-#[derive(Debug, PartialEq, Eq)]
-struct Coord {
-    kind: CoordKind,
-    x: RangeInclusive<usize>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Digit(u64),
+    Symbol(char),
 }
 
-impl Coord {
-    fn adjacent(&self, other: &Coord) -> bool {
-        let (large, small) = match (&self.kind, &other.kind) {
-            (CoordKind::Number(_), _) => (&self.x, &other.x),
-            (_, CoordKind::Number(_)) => (&other.x, &self.x),
-            _ => unreachable!("have to compare something to a number"),
-        };
-        // range of self must contain the start or end of the other x
-        // ....#..
-        // ..123..
-        // ...*...
-        if large.contains(small.start()) || large.contains(small.end()) {
-            return true;
+impl TryFrom<char> for Cell {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Cell::Empty),
+            c if c.is_ascii_digit() => Ok(Cell::Digit(c.to_digit(10).unwrap() as u64)),
+            c if c.is_ascii_punctuation() => Ok(Cell::Symbol(c)),
+            c => Err(c),
         }
-        // other x must be within 1 from the start or end
-        // .#....
-        // ..123.
-        // .....$
-        large.start().abs_diff(*small.end()) <= 1 || large.end().abs_diff(*small.start()) <= 1
     }
+}
+
+/// The engine schematic, with the numbers written in it pulled out.
+pub struct Schematic {
+    cells: Grid<Cell>,
+    numbers: Vec<u64>,
+    /// For each cell, the index in `numbers` of the number written over it.
+    owners: Grid<Option<usize>>,
+}
 
-    fn is_part(&self) -> bool {
-        matches!(self.kind, CoordKind::Symbol(_))
+impl Schematic {
+    /// The distinct numbers touching `pos`, including diagonally.
+    fn adjacent_numbers(&self, pos: Pos) -> Vec<usize> {
+        let mut adjacent = self
+            .cells
+            .neighbours8(pos)
+            .filter_map(|p| self.owners[p])
+            .collect::<Vec<_>>();
+        adjacent.sort();
+        adjacent.dedup();
+        adjacent
     }
+}
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Gear Ratios";
 
-    fn is_gear_symbol(&self) -> bool {
-        matches!(self.kind, CoordKind::Symbol('*'))
+    type Parsed = Schematic;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: crate::Input) -> crate::Result<Schematic> {
+        Ok(parse_schematic(input)?)
     }
 
-    fn is_number(&self) -> bool {
-        matches!(self.kind, CoordKind::Number(_))
+    fn part1(schematic: &Schematic) -> crate::Result<u64> {
+        Ok(part1(schematic))
     }
 
-    fn number(&self) -> u64 {
-        let CoordKind::Number(n) = self.kind else {
-            panic!("not a number")
-        };
-        n
+    fn part2(schematic: &Schematic) -> crate::Result<u64> {
+        Ok(part2(schematic))
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum CoordKind {
-    Nil,
-    Number(u64),
-    Symbol(char),
+pub fn solve1(input: crate::Input) -> crate::Result<u64> {
+    Ok(part1(&parse_schematic(input)?))
 }
 
-pub fn solve1(input: crate::Input) -> u64 {
-    let coords = parse_coords(input);
-    let mut adjacents = vec![];
-    let check_adjacent_row = |number: &Coord, y: usize| {
-        coords
-            .get(y)
-            .map(|row| {
-                row.iter()
-                    .filter(|c| c.is_part())
-                    .any(|c| number.adjacent(c))
-            })
-            .unwrap_or_default()
-    };
-    for (y, xs) in coords.iter().enumerate() {
-        adjacents.extend(
-            xs.iter()
-                .enumerate()
-                .filter(|(_, c)| matches!(c.kind, CoordKind::Number(_)))
-                .filter(|(i, number)| {
-                    let check_neighbor = |x| xs.get(x).map(Coord::is_part).unwrap_or_default();
-                    check_neighbor(i.wrapping_sub(1))
-                        || check_neighbor(i.wrapping_add(1))
-                        || check_adjacent_row(number, y.wrapping_sub(1))
-                        || check_adjacent_row(number, y.wrapping_add(1))
-                })
-                .map(|(_, c)| c),
-        );
-    }
-    adjacents
-        .into_iter()
-        .map(|c| match c.kind {
-            CoordKind::Number(x) => x,
-            _ => unreachable!(),
-        })
-        .inspect(|n| println!("{}", n))
+fn part1(schematic: &Schematic) -> u64 {
+    let mut is_part = vec![false; schematic.numbers.len()];
+    schematic
+        .cells
+        .iter()
+        .filter(|(_, c)| matches!(c, Cell::Symbol(_)))
+        .flat_map(|(pos, _)| schematic.adjacent_numbers(pos))
+        .for_each(|i| is_part[i] = true);
+    schematic
+        .numbers
+        .iter()
+        .zip(is_part)
+        .filter_map(|(n, is_part)| is_part.then_some(n))
         .sum()
 }
 
-pub fn solve2(input: crate::Input) -> u64 {
-    let coords = parse_coords(input);
-    let find_adjacent_numbers = |gear: &Coord, y: usize| {
-        coords.get(y).map_or_else(
-            || vec![],
-            |row| {
-                row.iter()
-                    .filter_map(|c2| (c2.is_number() && gear.adjacent(c2)).then(|| c2.number()))
-                    .collect()
-            },
-        )
-    };
-    let mut ratios = vec![];
-    for (y, row) in coords.iter().enumerate() {
-        ratios.extend(
-            row.iter()
-                .enumerate()
-                .filter(|(_, c)| c.is_gear_symbol())
-                .filter_map(|(i, gear)| {
-                    let check_neighbor =
-                        |i: usize| row.get(i).and_then(|c| c.is_number().then(|| c.number()));
-                    let up = find_adjacent_numbers(gear, y.wrapping_sub(1));
-                    let down = find_adjacent_numbers(gear, y + 1);
-                    let left = check_neighbor(i.wrapping_sub(1));
-                    let right = check_neighbor(i + 1);
-                    let all = up
-                        .into_iter()
-                        .chain(down.into_iter())
-                        .chain(left.into_iter())
-                        .chain(right.into_iter())
-                        .collect::<Vec<_>>();
-                    if all.len() != 2 {
-                        None
-                    } else {
-                        Some(all[0] * all[1])
-                    }
-                }),
-        )
-    }
-    ratios.into_iter().sum()
+pub fn solve2(input: crate::Input) -> crate::Result<u64> {
+    Ok(part2(&parse_schematic(input)?))
+}
+
+fn part2(schematic: &Schematic) -> u64 {
+    schematic
+        .cells
+        .iter()
+        .filter(|(_, c)| matches!(c, Cell::Symbol('*')))
+        .filter_map(|(pos, _)| match schematic.adjacent_numbers(pos)[..] {
+            [a, b] => Some(schematic.numbers[a] * schematic.numbers[b]),
+            _ => None,
+        })
+        .sum()
 }
 
-fn parse_coords(input: crate::Input) -> Vec<Vec<Coord>> {
-    let mut vv = vec![];
-    for (_, line) in input.enumerate() {
-        let mut v = vec![];
-        let mut chars = line.chars().enumerate().peekable();
-        while let Some((x, c)) = chars.next() {
-            let coord = match c {
-                '.' => Coord {
-                    kind: CoordKind::Nil,
-                    x: x..=x,
-                },
-                c if c.is_ascii_punctuation() => Coord {
-                    kind: CoordKind::Symbol(c),
-                    x: x..=x,
-                },
-                c if c.is_ascii_digit() => consume_number(&mut chars, x, &line),
-                _ => unreachable!(),
-            };
-            v.push(coord);
+fn parse_schematic(input: crate::Input) -> Result<Schematic, ParseError> {
+    let cells = Grid::<Cell>::parse(input, "expected '.', a digit or a symbol")?;
+    let mut numbers = vec![];
+    let mut owners = cells.map(|_| None);
+    for (row, cs) in cells.rows().enumerate() {
+        let mut col = 0;
+        while col < cs.len() {
+            let start = col;
+            while let Some(Cell::Digit(_)) = cs.get(col) {
+                col += 1;
+            }
+            if start == col {
+                col += 1;
+                continue;
+            }
+            let digits = cs[start..col]
+                .iter()
+                .map(|c| match c {
+                    Cell::Digit(d) => *d,
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>();
+            let number = digits
+                .iter()
+                .try_fold(0u64, |n, d| n.checked_mul(10)?.checked_add(*d))
+                .ok_or_else(|| {
+                    let text = digits.iter().map(u64::to_string).collect::<String>();
+                    ParseError::new(row + 1, start + 1, text, "number too large")
+                })?;
+            (start..col).for_each(|c| owners[Pos::new(row, c)] = Some(numbers.len()));
+            numbers.push(number);
         }
-        vv.push(v);
     }
-    vv
+    Ok(Schematic {
+        cells,
+        numbers,
+        owners,
+    })
 }
 
-fn consume_number(
-    chars: &mut Peekable<impl Iterator<Item = (usize, char)>>,
-    start: usize,
-    line: &str,
-) -> Coord {
-    let parse_number = |end| CoordKind::Number(line[start..=end].parse::<u64>().unwrap());
-    match chars.peek() {
-        None => {
-            let end = line.len() - 1;
-            return Coord {
-                kind: parse_number(line.len() - 1),
-                x: start..=end,
-            };
-        }
-        Some((x, c)) if !c.is_ascii_digit() => {
-            let end = *x - 1;
-            return Coord {
-                kind: parse_number(end),
-                x: start..=end,
-            };
-        }
-        Some((_, c)) if c.is_ascii_digit() => {
-            let _ = chars.next();
-            consume_number(chars, start, line)
-        }
-        _ => unreachable!(),
+#[cfg(test)]
+mod tests {
+    use super::{solve1, solve2};
+
+    const EXAMPLE: &str = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 4361);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 467835);
+    }
+
+    #[test]
+    fn test_numbers_at_line_edges() {
+        assert_eq!(solve1("12.\n..*\n..7".into()).unwrap(), 19);
+        assert_eq!(solve2("12.\n..*\n..7".into()).unwrap(), 84);
+    }
+
+    #[test]
+    fn test_number_too_large() {
+        let e = solve1(".123456789012345678901\n*.....................".into()).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "parse error: line 1, column 2: number too large: \"123456789012345678901\""
+        );
     }
 }
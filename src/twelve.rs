@@ -1,14 +1,54 @@
-use std::collections::HashMap;
+use std::iter;
 
-use crate::Input;
+use rand::Rng;
 
-pub fn solve1(input: Input) -> usize {
-    solve(parse_input(input))
+use crate::{Input, ParseError};
+
+/// Every row of the condition records.
+pub struct Records(Vec<Record>);
+
+impl Records {
+    /// The rows as they are when unfolded for part 2.
+    pub fn unfolded(&self) -> Records {
+        Records(self.0.iter().map(combinatorial_explosion_bait).collect())
+    }
+
+    /// The arrangements of each row.
+    pub fn arrangements(&self) -> impl Iterator<Item = crate::Result<Arrangements<'_>>> {
+        self.0
+            .iter()
+            .map(|(states, groups)| Arrangements::new(states, groups))
+    }
+}
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 12;
+    const TITLE: &'static str = "Hot Springs";
+
+    type Parsed = Records;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: Input) -> crate::Result<Records> {
+        Ok(Records(parse_input(input)?))
+    }
+
+    fn part1(Records(records): &Records) -> crate::Result<usize> {
+        solve(records.iter().cloned())
+    }
+
+    fn part2(Records(records): &Records) -> crate::Result<usize> {
+        solve(records.iter().map(combinatorial_explosion_bait))
+    }
 }
 
-fn combinatorial_explosion_bait(
-    (states, ref targets): (Vec<SpringState>, Vec<usize>),
-) -> (Vec<SpringState>, Vec<usize>) {
+pub fn solve1(input: Input) -> crate::Result<usize> {
+    solve(parse_input(input)?.into_iter())
+}
+
+fn combinatorial_explosion_bait((states, targets): &Record) -> Record {
     let states = states.as_slice();
     let states = [states, states, states, states, states].join(&Unknown);
     let targets = [targets, targets, targets, targets, targets]
@@ -19,26 +59,23 @@ fn combinatorial_explosion_bait(
     (states, targets)
 }
 
-pub fn solve2(input: Input) -> usize {
-    solve(
-        parse_input(input)
-            .into_iter()
-            .map(|t| combinatorial_explosion_bait(t)),
-    )
+pub fn solve2(input: Input) -> crate::Result<usize> {
+    solve(parse_input(input)?.iter().map(combinatorial_explosion_bait))
 }
 
-fn solve(input: impl Iterator<Item = (Vec<SpringState>, Vec<usize>)>) -> usize {
-    let mut memoize = Map::new();
-    let input = input.collect::<Vec<_>>();
-    let mut sum = 0;
-    input
-        .iter()
-        .for_each(|(states, targets)| sum += arrangements(&mut memoize, states, targets));
-    sum
+fn solve(input: impl Iterator<Item = Record>) -> crate::Result<usize> {
+    let mut sum = 0usize;
+    for (states, targets) in input {
+        let count = Arrangements::new(&states, &targets)?.count();
+        sum = sum.checked_add(count).ok_or_else(|| {
+            crate::Error::Unsolvable("the arrangements add up to more than a usize holds".into())
+        })?;
+    }
+    Ok(sum)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum SpringState {
+pub enum SpringState {
     Operational,
     Damaged,
     Unknown,
@@ -46,19 +83,24 @@ enum SpringState {
 
 use SpringState::*;
 
-impl From<char> for SpringState {
-    fn from(c: char) -> Self {
+/// A row of springs and the sizes of its damaged groups.
+type Record = (Vec<SpringState>, Vec<usize>);
+
+impl TryFrom<char> for SpringState {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            '.' => Operational,
-            '#' => Damaged,
-            '?' => Unknown,
-            _ => unreachable!(),
+            '.' => Ok(Operational),
+            '#' => Ok(Damaged),
+            '?' => Ok(Unknown),
+            c => Err(c),
         }
     }
 }
 
-#[allow(dead_code)]
-fn debug_string(states: &[SpringState]) -> String {
+/// The springs as they are written in the records.
+pub fn debug_string(states: &[SpringState]) -> String {
     states.iter().fold(String::new(), |mut s, ss| {
         match ss {
             Operational => s.push('.'),
@@ -69,71 +111,267 @@ fn debug_string(states: &[SpringState]) -> String {
     })
 }
 
-type K<'a> = (&'a [SpringState], &'a [usize]);
-type Map<'a> = HashMap<K<'a>, usize>;
-
-fn arrangements<'a>(
-    memoize: &mut Map<'a>,
+/// The ways to fill in the unknown springs of a row so that its damaged
+/// groups come out right, counted once up front.
+///
+/// `ways[i][j]` is the number of ways to fill in `states[i..]` so that it holds
+/// exactly the groups `groups[j..]`. Each filling is numbered by the choices it
+/// makes from the left, an operational spring before a damaged group, so the
+/// table can also list them in order or pick one out by number.
+///
+/// A tail that no filling of the whole row reaches can have more ways than
+/// the row itself, so a cell is `None` where its count overflows. Any cell the
+/// row does reach counts at most as many ways as the row.
+pub struct Arrangements<'a> {
     states: &'a [SpringState],
-    targets: &'a [usize],
-) -> usize {
-    fn f<'a>(memoize: &mut Map<'a>, states: &'a [SpringState], targets: &'a [usize]) -> usize {
-        if (states.len() as isize)
-            < (targets.len() as isize - 1 + targets.iter().sum::<usize>() as isize)
-        {
-            return 0;
+    groups: &'a [usize],
+    ways: Vec<Vec<Option<usize>>>,
+}
+
+impl<'a> Arrangements<'a> {
+    /// Fails if the row has more arrangements than a `usize` holds.
+    pub fn new(states: &'a [SpringState], groups: &'a [usize]) -> crate::Result<Arrangements<'a>> {
+        let (n, m) = (states.len(), groups.len());
+        let mut arrangements = Arrangements {
+            states,
+            groups,
+            ways: vec![vec![Some(0); m + 1]; n + 1],
+        };
+        arrangements.ways[n][m] = Some(1);
+        for i in (0..n).rev() {
+            for j in 0..=m {
+                arrangements.ways[i][j] =
+                    arrangements.choices(i, j).try_fold(0usize, |sum, (i, j)| {
+                        sum.checked_add(arrangements.ways[i][j]?)
+                    });
+            }
         }
-        if states.is_empty() {
-            return if targets.is_empty() { 1 } else { 0 };
+        if arrangements.ways[0][0].is_none() {
+            return Err(crate::Error::Unsolvable(format!(
+                "{} has more arrangements than a usize holds",
+                debug_string(states)
+            )));
         }
-        if targets.is_empty() {
-            return if states.iter().any(|s| *s == Damaged) {
-                0
-            } else {
-                1
-            };
+        Ok(arrangements)
+    }
+
+    pub fn count(&self) -> usize {
+        self.ways[0][0].expect("new checks the row's count")
+    }
+
+    /// The filling numbered `k`, with every spring either operational or
+    /// damaged.
+    pub fn get(&self, mut k: usize) -> Option<Vec<SpringState>> {
+        if k >= self.count() {
+            return None;
         }
-        match states[0] {
-            Damaged => damaged(memoize, states, targets.split_first().unwrap()),
-            Operational => arrangements(memoize, &states[1..], targets),
-            Unknown => {
-                damaged(memoize, states, targets.split_first().unwrap())
-                    + arrangements(memoize, &states[1..], targets)
-            }
+        let mut springs = Vec::with_capacity(self.states.len());
+        let (mut i, mut j) = (0, 0);
+        while i < self.states.len() {
+            let (next_i, next_j) = self
+                .choices(i, j)
+                .find(|&(i, j)| {
+                    let ways = self.ways[i][j].expect("a reached cell counts no more than the row");
+                    match k.checked_sub(ways) {
+                        Some(rest) => {
+                            k = rest;
+                            false
+                        }
+                        None => true,
+                    }
+                })
+                .expect("a count is the sum of the counts it chooses between");
+            let damaged = if next_j > j { self.groups[j] } else { 0 };
+            springs.extend(iter::repeat_n(Damaged, damaged));
+            springs.extend(iter::repeat_n(Operational, next_i - i - damaged));
+            (i, j) = (next_i, next_j);
         }
+        Some(springs)
     }
-    let k = &(states, targets);
-    let x = if memoize.contains_key(k) {
-        *memoize.get(k).unwrap()
-    } else {
-        let v = f(memoize, states, targets);
-        memoize.insert(*k, v);
-        *memoize.get(&k).unwrap()
-    };
-    x
-}
 
-fn damaged<'a>(
-    memoize: &mut Map<'a>,
-    states: &'a [SpringState],
-    (target_run, rest_targets): (&usize, &'a [usize]),
-) -> usize {
-    let (run, states) = states.split_at(*target_run);
-    if run.iter().any(|s| *s == Operational) {
-        return 0;
+    /// Every filling, in order, each built only when it is reached.
+    pub fn iter(&self) -> impl Iterator<Item = Vec<SpringState>> + '_ {
+        (0..self.count()).map(|k| self.get(k).expect("k is below the count"))
     }
-    match states.split_first() {
-        Some((&Damaged, _)) => 0,
-        Some((_, states)) => arrangements(memoize, states, rest_targets),
-        None => arrangements(memoize, states, rest_targets),
+
+    /// One filling chosen uniformly at random, if there are any.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Vec<SpringState>> {
+        match self.count() {
+            0 => None,
+            count => self.get(rng.gen_range(0..count)),
+        }
+    }
+
+    /// Where filling in spring `i` can lead while group `j` is next: on past an
+    /// operational spring, or past group `j` and the operational spring that
+    /// ends it.
+    fn choices(&self, i: usize, j: usize) -> impl Iterator<Item = (usize, usize)> {
+        let n = self.states.len();
+        let gap = (self.states[i] != Damaged).then_some((i + 1, j));
+        let run = self.groups.get(j).and_then(|&len| {
+            let end = i + len;
+            let fits = end <= n
+                && !self.states[i..end].contains(&Operational)
+                && self.states.get(end) != Some(&Damaged);
+            fits.then_some(((end + 1).min(n), j + 1))
+        });
+        gap.into_iter().chain(run)
     }
 }
 
-fn parse_input(input: Input) -> impl Iterator<Item = (Vec<SpringState>, Vec<usize>)> {
-    input.map(|l| {
-        let (states, targets) = l.split_once(" ").unwrap();
-        let states = states.chars().map(SpringState::from).collect();
-        let targets = targets.split(',').filter_map(|n| n.parse().ok()).collect();
-        (states, targets)
-    })
+fn parse_input(input: Input) -> Result<Vec<Record>, ParseError> {
+    fn parse_line(n: usize, l: &str) -> Result<Record, ParseError> {
+        let (states, targets) = l
+            .split_once(' ')
+            .ok_or_else(|| ParseError::at(n, l, l, "expected '<springs> <groups>'"))?;
+        let states = states
+            .chars()
+            .enumerate()
+            .map(|(col, c)| {
+                SpringState::try_from(c)
+                    .map_err(|c| ParseError::new(n, col + 1, c, "expected '.', '#' or '?'"))
+            })
+            .collect::<Result<_, _>>()?;
+        let targets = targets
+            .split(',')
+            .map(|t| crate::parse(n, l, t))
+            .collect::<Result<_, _>>()?;
+        Ok((states, targets))
+    }
+    input.numbered().map(|(n, l)| parse_line(n, &l)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::{debug_string, parse_input, solve1, solve2, Arrangements, Records, SpringState};
+
+    const EXAMPLE: &str = "\
+???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 21);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 525152);
+    }
+
+    #[test]
+    fn test_arrangements_per_row() {
+        let counts = [1, 4, 1, 1, 4, 10];
+        let unfolded = [1, 16384, 1, 16, 2500, 506250];
+        for ((line, count), unfolded) in EXAMPLE.lines().zip(counts).zip(unfolded) {
+            assert_eq!(solve1(line.into()).unwrap(), count, "{}", line);
+            assert_eq!(solve2(line.into()).unwrap(), unfolded, "{}", line);
+        }
+    }
+
+    fn groups(springs: &[SpringState]) -> Vec<usize> {
+        debug_string(springs)
+            .split('.')
+            .filter(|run| !run.is_empty())
+            .map(str::len)
+            .collect()
+    }
+
+    #[test]
+    fn test_iter_matches_brute_force() {
+        for (states, targets) in parse_input(EXAMPLE.into()).unwrap() {
+            let unknown = states
+                .iter()
+                .filter(|&&s| s == SpringState::Unknown)
+                .count();
+            let expected = (0..1u32 << unknown)
+                .map(|bits| {
+                    let mut bit = 0;
+                    states
+                        .iter()
+                        .map(|&s| match s {
+                            SpringState::Unknown => {
+                                bit += 1;
+                                if bits >> (bit - 1) & 1 == 1 {
+                                    SpringState::Damaged
+                                } else {
+                                    SpringState::Operational
+                                }
+                            }
+                            s => s,
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .filter(|springs| groups(springs) == targets)
+                .map(|springs| debug_string(&springs))
+                .collect::<Vec<_>>();
+            let arrangements = Arrangements::new(&states, &targets).unwrap();
+            let mut found = arrangements
+                .iter()
+                .map(|springs| debug_string(&springs))
+                .collect::<Vec<_>>();
+            assert!(found.windows(2).all(|w| w[0] > w[1]), "{:?}", found);
+            found.reverse();
+            let mut expected = expected;
+            expected.sort();
+            assert_eq!(found, expected);
+            assert_eq!(arrangements.get(arrangements.count()), None);
+        }
+    }
+
+    #[test]
+    fn test_iter_is_lazy() {
+        let records = Records(parse_input("?###???????? 3,2,1".into()).unwrap()).unfolded();
+        let arrangements = records.arrangements().next().unwrap().unwrap();
+        assert_eq!(arrangements.count(), 506250);
+        let first = arrangements.iter().next().unwrap();
+        assert_eq!(
+            debug_string(&first),
+            ".###.....##.#.###.....##.#.###.....##.#.###.....##.#.###....##.#"
+        );
+        let last = arrangements.get(506249).unwrap();
+        assert_eq!(
+            debug_string(&last),
+            ".###.##.#.....###.##.#.....###.##.#.....###.##.#.....###.##.#..."
+        );
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = SmallRng::seed_from_u64(12);
+        let records = Records(parse_input(EXAMPLE.into()).unwrap()).unfolded();
+        for ((states, targets), arrangements) in records.0.iter().zip(records.arrangements()) {
+            let arrangements = arrangements.unwrap();
+            for _ in 0..20 {
+                let springs = arrangements.sample(&mut rng).unwrap();
+                assert_eq!(groups(&springs), *targets);
+                assert!(states
+                    .iter()
+                    .zip(&springs)
+                    .all(|(&s, &f)| s == SpringState::Unknown || s == f));
+            }
+        }
+        let none = Records(parse_input("#.# 3".into()).unwrap());
+        let none = none.arrangements().next().unwrap().unwrap();
+        assert_eq!(none.sample(&mut rng), None);
+    }
+
+    #[test]
+    fn test_too_many_arrangements() {
+        let line = format!("{} 1,1,1,1,1", "?".repeat(60));
+        assert_eq!(solve1(line.as_str().into()).unwrap(), 3_819_816);
+        let e = solve2(line.as_str().into()).unwrap_err();
+        assert!(
+            e.to_string()
+                .ends_with("has more arrangements than a usize holds"),
+            "{}",
+            e
+        );
+    }
 }
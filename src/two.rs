@@ -1,74 +1,191 @@
-#[derive(Default, Debug)]
-struct Set {
-    red: u32,
-    blue: u32,
-    green: u32,
+use std::collections::{BTreeMap, BTreeSet};
+
+use num::BigUint;
+
+use crate::{solver::Params, ParseError};
+
+/// How many cubes of each color there are.
+type Set = BTreeMap<String, u32>;
+
+/// A game's number and the sets of cubes revealed during it.
+pub struct Game {
+    number: usize,
+    sets: Vec<Set>,
 }
 
-impl Set {
-    fn power(&self) -> u32 {
-        self.red * self.blue * self.green
+/// The bag the elf asks about in part 1.
+const BAG: &str = "red=12,green=13,blue=14";
+
+pub struct Solution;
+
+impl crate::Solver for Solution {
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "Cube Conundrum";
+
+    type Parsed = Vec<Game>;
+    type Answer1 = usize;
+    type Answer2 = BigUint;
+
+    fn parse(input: crate::Input) -> crate::Result<Vec<Game>> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(games: &Vec<Game>) -> crate::Result<usize> {
+        Ok(part1(games, &parse_bag(BAG)?))
+    }
+
+    /// Takes the bag's contents as `--bag <color>=<count>,...`.
+    fn part1_with(games: &Vec<Game>, params: &Params) -> crate::Result<usize> {
+        params.expect_only(&["bag"])?;
+        Ok(part1(games, &parse_bag(params.get("bag").unwrap_or(BAG))?))
+    }
+
+    fn part2(games: &Vec<Game>) -> crate::Result<BigUint> {
+        Ok(part2(games))
     }
 }
 
-pub fn solve1(input: crate::Input) -> usize {
-    const BAG: Set = Set {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
-    input
-        .filter_map(|line| {
-            let (n, mut game) = parse_line(&line);
-            game.all(|set| set.blue <= BAG.blue && set.green <= BAG.green && set.red <= BAG.red)
-                .then_some(n)
+pub fn solve1(input: crate::Input) -> crate::Result<usize> {
+    Ok(part1(&parse_input(input)?, &parse_bag(BAG)?))
+}
+
+/// Sums the numbers of the games that could have been played with `bag`.
+/// Colors missing from the bag have no cubes.
+fn part1(games: &[Game], bag: &Set) -> usize {
+    games
+        .iter()
+        .filter(|game| {
+            game.sets.iter().all(|set| {
+                set.iter()
+                    .all(|(color, count)| *count <= bag.get(color).copied().unwrap_or(0))
+            })
         })
+        .map(|game| game.number)
         .sum()
 }
 
-fn parse_line(line: &str) -> (usize, impl Iterator<Item = Set> + '_) {
-    let (n, details) = game_number_detais(line);
-    let details = details.split("; ").map(|subset| {
-        subset
-            .split(", ")
-            .fold(Set::default(), |set, s| match s.split_once(" ").unwrap() {
-                (n, "blue") => Set {
-                    blue: n.parse().unwrap(),
-                    ..set
-                },
-                (n, "red") => Set {
-                    red: n.parse().unwrap(),
-                    ..set
-                },
-                (n, "green") => Set {
-                    green: n.parse().unwrap(),
-                    ..set
-                },
-                _ => unreachable!(),
-            })
-    });
-    (n, details)
+fn parse_bag(bag: &str) -> crate::Result<Set> {
+    let invalid = |reason: &str| crate::Error::Param(format!("--bag {}: {}", bag, reason));
+    bag.split(',')
+        .map(|cubes| {
+            let (color, count) = cubes
+                .split_once('=')
+                .ok_or_else(|| invalid("expected '<color>=<count>,...'"))?;
+            let count = count.parse().map_err(|_| invalid("expected a count"))?;
+            Ok((color.to_string(), count))
+        })
+        .collect()
 }
 
-fn game_number_detais(line: &str) -> (usize, &str) {
-    let (game, details) = line.split_once(": ").unwrap();
+fn parse_input(input: crate::Input) -> Result<Vec<Game>, ParseError> {
+    input
+        .numbered()
+        .map(|(n, line)| parse_line(n, &line))
+        .collect()
+}
+
+fn parse_line(n: usize, line: &str) -> Result<Game, ParseError> {
+    let (number, details) = game_number_detais(n, line)?;
+    let sets = details
+        .split("; ")
+        .map(|subset| {
+            subset
+                .split(", ")
+                .map(|s| {
+                    let (count, color) = s
+                        .split_once(' ')
+                        .ok_or_else(|| ParseError::at(n, line, s, "expected '<count> <color>'"))?;
+                    Ok((color.to_string(), crate::parse(n, line, count)?))
+                })
+                .collect::<Result<Set, _>>()
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Game { number, sets })
+}
+
+fn game_number_detais(n: usize, line: &str) -> Result<(usize, &str), ParseError> {
+    let (game, details) = line
+        .split_once(": ")
+        .ok_or_else(|| ParseError::at(n, line, line, "expected 'Game <n>: '"))?;
     let game = game
         .strip_prefix("Game ")
-        .and_then(|n| n.parse::<usize>().ok())
-        .unwrap();
-    (game, details)
+        .ok_or_else(|| ParseError::at(n, line, game, "expected 'Game <n>'"))?;
+    Ok((crate::parse(n, line, game)?, details))
 }
 
-pub fn solve2(input: crate::Input) -> u32 {
-    input
-        .map(|line| {
-            let (_, sets) = parse_line(&line);
-            sets.fold(Set::default(), |fewest_bag, set| Set {
-                red: fewest_bag.red.max(set.red),
-                blue: fewest_bag.blue.max(set.blue),
-                green: fewest_bag.green.max(set.green),
-            })
+pub fn solve2(input: crate::Input) -> crate::Result<BigUint> {
+    Ok(part2(&parse_input(input)?))
+}
+
+/// Sums the power of the fewest cubes each game could have been played with:
+/// the product of the counts of every color seen anywhere in the input, so
+/// that a game which never shows one of them has power 0. The sum is a
+/// [`BigUint`], since with enough colors the products outgrow any fixed-width
+/// integer.
+fn part2(games: &[Game]) -> BigUint {
+    let colors = games
+        .iter()
+        .flat_map(|game| game.sets.iter().flat_map(|set| set.keys()))
+        .collect::<BTreeSet<_>>();
+    games
+        .iter()
+        .map(|game| {
+            let mut fewest = Set::new();
+            for (color, count) in game.sets.iter().flatten() {
+                let most = fewest.entry(color.clone()).or_default();
+                *most = (*most).max(*count);
+            }
+            colors
+                .iter()
+                .map(|&color| BigUint::from(fewest.get(color).copied().unwrap_or(0)))
+                .product::<BigUint>()
         })
-        .map(|set| set.power())
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{solve1, solve2, Solution};
+    use crate::{solver::Params, Solver};
+
+    const EXAMPLE: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_solve1_example() {
+        assert_eq!(solve1(EXAMPLE.into()).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_solve2_example() {
+        assert_eq!(solve2(EXAMPLE.into()).unwrap(), 2286u32.into());
+    }
+
+    #[test]
+    fn test_other_colors() {
+        let input = "\
+Game 1: 3 blue, 4 purple; 1 red
+Game 2: 2 red, 2 blue; 1 purple";
+        assert_eq!(solve1(input.into()).unwrap(), 0);
+        assert_eq!(solve2(input.into()).unwrap(), (12u32 + 4).into());
+        let games = Solution::parse(input.into()).unwrap();
+        let bag = |b: &str| Params::new([("bag".to_string(), b.to_string())]);
+        let with = |b| Solution::part1_with(&games, &bag(b));
+        assert_eq!(with("red=2,blue=3,purple=4").unwrap(), 3);
+        assert_eq!(with("red=2,blue=2,purple=1").unwrap(), 2);
+        assert_eq!(
+            with("red:2").unwrap_err().to_string(),
+            "parameter error: --bag red:2: expected '<color>=<count>,...'"
+        );
+    }
+
+    #[test]
+    fn test_power_beyond_u32() {
+        let input = "Game 1: 1000 a, 1000 b, 1000 c, 1000 d";
+        assert_eq!(solve2(input.into()).unwrap(), 1_000_000_000_000u64.into());
+    }
+}